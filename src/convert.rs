@@ -1,33 +1,78 @@
 use crate::utils::{
-    calc::{eval, meval},
-    currency::curr_convert_q,
-    translate::Translator,
+    calc::{
+        complex_convert_with, evaluate_with, factor_convert_with, float_convert_with, meval_with,
+        radix_convert_with, solve_with,
+    },
+    currency::Converter,
+    exact::ExactMeasurement,
+    format::FormatOptions,
     units::Measurement,
 };
-use anyhow::{bail, Result};
+use crate::{Error, Result};
 
 /// This is magic function that's used to convert units and translate text
 /// Use that **format** for translate `lg to lg text` or `lg:lg text` where `lg` is language code.
 /// Use that **format** for convert `nu to u` or `nu:u` where `n` is number and `u` is unit.
-pub async fn magic_convert(query: &String) -> Result<String> {
-    let measure_response = Measurement::convert(query);
-    let tr = Translator::new();
+pub async fn magic_convert(query: &str) -> Result<String> {
+    magic_convert_with(query, &FormatOptions::default()).await
+}
+
+/// Same as `magic_convert`, but numbers in the result are printed using given format options.
+/// Numbers in the query are read using `options.locale`.
+pub async fn magic_convert_with(query: &str, options: &FormatOptions) -> Result<String> {
+    magic_convert_using(query, options, &Converter::default()).await
+}
+
+/// Same as `magic_convert_with`, but units are converted with exact rational factors,
+/// so only the printed result is rounded.
+pub async fn magic_convert_exact(query: &str, options: &FormatOptions) -> Result<String> {
+    if let Ok(resp) = ExactMeasurement::convert_with(query, &options.locale) {
+        return Ok(resp.txt_with(options));
+    }
+    magic_convert_with(query, options).await
+}
+
+/// Same as `magic_convert_with`, but currencies and text are converted with given converter.
+pub async fn magic_convert_using(
+    query: &str,
+    options: &FormatOptions,
+    converter: &Converter,
+) -> Result<String> {
+    let measure_response = Measurement::convert_with(query, &options.locale);
     if let Ok(resp) = measure_response {
+        return Ok(resp.txt_with(options));
+    }
+    if let Ok(resp) = radix_convert_with(query, &options.locale) {
         return Ok(resp.txt());
     }
-    if let Ok(resp) = eval(query) {
-        return Ok(resp.to_string());
+    if let Ok(resp) = solve_with(query, &options.locale) {
+        return Ok(resp.txt_with(options));
     }
-    if let Ok(resp) = meval(query) {
+    if let Ok(resp) = factor_convert_with(query, &options.locale) {
         return Ok(resp.txt());
     }
-    if let Ok(resp) = curr_convert_q(query).await {
+    if let Ok(resp) = float_convert_with(query, &options.locale) {
         return Ok(resp.txt());
     }
-    let translate_response = tr.convert(query).await?;
-    if translate_response != *query {
-        return Ok(translate_response);
+    if let Ok(resp) = complex_convert_with(query, &options.locale) {
+        return Ok(resp.txt_with(options));
+    }
+    if let Ok(resp) = evaluate_with(query, &options.locale) {
+        return Ok(resp.txt_with(options));
+    }
+    if let Ok(resp) = meval_with(query, &options.locale) {
+        return Ok(resp.txt_with(options));
+    }
+    if let Ok(resp) = converter.convert_query_many(query, &options.locale).await {
+        let results: Vec<String> = resp.iter().map(|c| c.txt_with(options)).collect();
+        return Ok(results.join(", "));
+    }
+    match converter.translator().convert(query).await {
+        Ok(translate_response) if translate_response != query => return Ok(translate_response),
+        // Network failures are reported, the query may work later.
+        Err(error) if error.is_transient() => return Err(error),
+        _ => {}
     }
 
-    bail!("Sorry, I can't understand that query.")
+    Err(Error::NotUnderstood)
 }
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use crate::utils::diagnostic::ParseError;
+
+/// Result with the library error.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Error of a conversion, calculation or translation.
+/// Match on the variant to tell a typo in the query from a network failure.
+#[derive(Debug)]
+pub enum Error {
+    /// Query or expression can't be parsed, the error points to the mistake.
+    Parse(ParseError),
+    /// Query doesn't have the expected form, like `solve` without an equation.
+    InvalidQuery(String),
+    /// Expression is parsed but can't be computed, like `1/0` or `(-1)!`.
+    Math(String),
+    /// Values of different dimensions are combined, like `3 m + 2 kg`.
+    IncompatibleUnits(String),
+    /// Unit symbol is unknown, the error has suggestions of known symbols.
+    UnknownUnit(ParseError),
+    /// Currency code is unknown, the error has suggestions of known codes.
+    UnknownCurrency(ParseError),
+    /// Language code is not valid.
+    UnknownLanguage(String),
+    /// Request to a rates or translation service failed.
+    Network(reqwest::Error),
+    /// Service answered with data that can't be used, like a missing rate.
+    Provider(String),
+    /// Service refused the request because of too many requests.
+    RateLimited,
+    /// Service didn't answer in time.
+    Timeout,
+    /// No converter understood the query.
+    NotUnderstood,
+}
+
+impl Error {
+    /// Returns the parse error with the place of the mistake, if the query couldn't be parsed.
+    pub fn parse_error(&self) -> Option<&ParseError> {
+        match self {
+            Error::Parse(error) | Error::UnknownUnit(error) | Error::UnknownCurrency(error) => {
+                Some(error)
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn parse_error_mut(&mut self) -> Option<&mut ParseError> {
+        match self {
+            Error::Parse(error) | Error::UnknownUnit(error) | Error::UnknownCurrency(error) => {
+                Some(error)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns is the error caused by the network or the service, so the query may work later.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Error::Network(_) | Error::RateLimited | Error::Timeout
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(error) | Error::UnknownUnit(error) | Error::UnknownCurrency(error) => {
+                write!(f, "{}", error)
+            }
+            Error::InvalidQuery(message)
+            | Error::Math(message)
+            | Error::IncompatibleUnits(message) => write!(f, "{}", message),
+            Error::UnknownLanguage(code) => write!(f, "Unknown language '{}'.", code),
+            Error::Network(error) => write!(f, "Network error: {}", error),
+            Error::Provider(message) => write!(f, "Provider error: {}", message),
+            Error::RateLimited => write!(f, "Too many requests, try again later."),
+            Error::Timeout => write!(f, "The service didn't answer in time."),
+            Error::NotUnderstood => write!(f, "Sorry, I can't understand that query."),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(error) | Error::UnknownUnit(error) | Error::UnknownCurrency(error) => {
+                Some(error)
+            }
+            Error::Network(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Error::Timeout
+        } else if error.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+            Error::RateLimited
+        } else {
+            Error::Network(error)
+        }
+    }
+}
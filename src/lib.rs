@@ -16,6 +16,31 @@
 //! ## What's new in release ?
 //! - Translate bug fix ('to' and ':' issue)
 //! - Convert currencies(available in magic_convert)
+//! - Precision and rounding control for results (`FormatOptions`, `magic_convert_with`)
+//! - Locale-aware numbers (`1,5 km`, `1 000 000 B`, `1e6 m`) with `Locale`
+//! - Unit and currency names in English, German, Spanish, French, Russian and Ukrainian (`FormatOptions::localized`)
+//! - Exact rational unit conversions (`ExactMeasurement`, `magic_convert_exact`)
+//! - Big integers and exact decimals in the calculator (`2^100`, `20!`), see `calc::evaluate`
+//! - Complex numbers (`sqrt(-4)`, `(3+4i)*(1-2i)`, `3+4i to polar`)
+//! - Programmer mode: `0xff`/`0b1010`/`0o17` literals, bitwise `& | ^ ~ << >>`, `255 to hex`, `-1 as u16`
+//! - IEEE-754 float inspection (`0.1 to f32 bits`, `1.5 to f64 hex`, `0x3f800000 as f32`)
+//! - Statistics over lists (`mean(3, 5, 9)`, `median([...])`, `stdev`, `variance`, `sum`, `product`, `percentile(list, 90)`, `mean(3m, 250cm, 4000mm)`)
+//! - Combinatorics and number theory with big integers (`nCr(10, 3)`, `nPr`, `gcd`, `lcm`, `17 mod 5`, `isprime`, `factor(360)`, `fib(100)`)
+//! - Implicit multiplication and unit expressions (`2 * 3m`, `(1km + 200m) / 4`, `2pi rad`, `3 m 20 cm`), angles in `rad` and `deg`
+//! - Convert the result of an expression (`(5km + 300m) * 2 to mi`, `12V * 2A to kW`), miles, yards and feet
+//! - Equation solver (`solve x: 2x + 3 = 11`, `solve x: x^2 - 4 = 0`, `solve t: 100km = 80km/h * t to min`)
+//! - Parse errors with the place of the mistake and suggestions (`5 kmh` → did you mean `km/h`?), see `diagnostic::ParseError`
+//! - Typed errors: `convers::Error` with variants for parse errors, incompatible units, unknown units, currencies and languages, network failures, rate limits and timeouts
+//! - Pluggable exchange rates: `RateProvider` with open.er-api.com, ECB, static/JSON and closure providers, selected with `currency::Converter`
+//! - Configurable base URL and `reqwest::Client` for rate providers and `Translator` (`with_base_url`, `with_client`), `Converter::with_translator`
+//! - Exchange rates are cached (`RateCache`) until the provider's next update, optionally on disk in the XDG cache dir, and used offline with their age in `Currency::age`
+//! - Cross rates: any currency pair is computed from one cached base table (`RateTable::cross_rate`, `Converter::with_base`)
+//! - ISO 4217 registry (`iso4217`) with numeric codes, symbols, minor units and aliases: `$5 to €`, `5 bucks to yen`, `100 hryvnia in usd`, results rounded to minor units
+//! - Money is a fixed-point `Decimal` (`Currency::amount`) with explicit rounding to minor units (`Currency::rounded`) and sign placement by locale (`$1,234.56`, `1 234,56 ₴`) with `FormatOptions::symbols`
+//! - Currency arithmetic: `5 usd + 3 eur to gbp`, `(1200 usd / 12) to uah`, `20% of 300 eur in usd` (`Converter::evaluate`, `calc::money_eval`), percents with `%` and `of` in the calculator
+//! - Several targets in one query from one rate table: `100 usd to eur, gbp, jpy`, `100 usd to *favourites` (`Converter::with_favourites`, `convert_query_many`, `curr_convert_q_many`)
+//! - Historical rates: `100 usd to eur on 2024-03-01`, `curr_convert_at`, from the ECB history (`EcbHistory`) or a local CSV (`History::from_csv_file`), see `Converter::with_history`
+//! - Crypto and metals: `0.01 btc to usd`, `1 gwei to eth`, `10 g gold to eur`, sub-units (`sat`, `gwei`, `wei`) converted offline (`assets`), prices from CoinGecko (`Converter::with_assets`)
 //! ## Also you can use provided things directly:
 //! - _Translator_ *struct*
 //! - _Measurement_ *struct*
@@ -25,10 +50,10 @@
 //! use convers::utils::units::{Measurement, Unit};
 //! let a = Measurement::new(50.0, Unit::Meter);
 //! let b = Measurement::new(1.0, Unit::Kilometer);
-//! let c = a + b; // anyhow::Result<Measurement>. If unwrap it equals 1.050 Kilometer
+//! let c = a + b; // convers::Result<Measurement>. If unwrap it equals 1.050 Kilometer
 //! ```
 //! ## Current available conversions:
-//! - **Units**: Length, Mass, Amperes, Watts, Size (like kb), Rem/Px/Em,Temperature, Pressure, Speed, Volume, Area
+//! - **Units**: Length, Mass, Amperes, Watts, Size (like kb), Rem/Px/Em,Temperature, Pressure, Speed, Volume, Area, Angle
 //!- **Translate**
 //! - **Translate**
 //! - **Currencies**
@@ -40,4 +65,7 @@
 //! - **CLI convers binary package to crates.io and AUR**
 
 pub mod convert;
+mod error;
 pub mod utils;
+
+pub use error::{Error, Result};
@@ -0,0 +1,83 @@
+use super::units::{Measurement, Unit};
+
+/// Grams in a troy ounce, precious metals are priced per troy ounce.
+pub const TROY_OUNCE_GRAMS: f64 = 31.1034768;
+
+/// Codes of crypto assets and metals. Fiat rate services usually don't have them,
+/// so they are asked from the asset provider of `Converter`.
+pub const ASSETS: [&str; 4] = ["BTC", "ETH", "XAG", "XAU"];
+
+/// Metals, their amounts can be given by weight like `10 g gold`.
+pub const METALS: [&str; 2] = ["XAG", "XAU"];
+
+/// Fixed sub-unit of an asset, like the satoshi of bitcoin.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Denomination {
+    /// Name printed in results, like `sat`.
+    pub name: &'static str,
+    /// Code of the asset, like `BTC`.
+    pub code: &'static str,
+    /// Amount of the asset in one sub-unit, like `0.00000001`.
+    pub factor: &'static str,
+    /// Other names used in queries, like `sats`.
+    pub aliases: &'static [&'static str],
+}
+
+/// Known sub-units, conversions between them and their assets don't need rates.
+pub const DENOMINATIONS: [Denomination; 5] = [
+    Denomination {
+        name: "sat",
+        code: "BTC",
+        factor: "0.00000001",
+        aliases: &["sats", "satoshi", "satoshis"],
+    },
+    Denomination {
+        name: "bits",
+        code: "BTC",
+        factor: "0.000001",
+        aliases: &["bit", "μBTC", "µBTC", "uBTC"],
+    },
+    Denomination {
+        name: "mBTC",
+        code: "BTC",
+        factor: "0.001",
+        aliases: &["millibitcoin"],
+    },
+    Denomination {
+        name: "gwei",
+        code: "ETH",
+        factor: "0.000000001",
+        aliases: &["shannon"],
+    },
+    Denomination {
+        name: "wei",
+        code: "ETH",
+        factor: "0.000000000000000001",
+        aliases: &[],
+    },
+];
+
+/// Returns sub-unit by its name or alias, case-insensitive, like `sats` or `Gwei`.
+pub fn denomination(name: &str) -> Option<&'static Denomination> {
+    let name = name.trim();
+    DENOMINATIONS.iter().find(|d| {
+        d.name.eq_ignore_ascii_case(name) || d.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Returns troy ounces in the mass, `None` for other units.
+/// `oz` is read as a troy ounce, as usual for metals.
+pub fn troy_ounces(mass: &Measurement) -> Option<f64> {
+    if mass.unit == Unit::Ounce {
+        return Some(mass.value);
+    }
+    let base = mass.to_base();
+    (base.unit == Unit::Kilogram).then(|| base.value * 1000. / TROY_OUNCE_GRAMS)
+}
+
+/// Returns names of all sub-units, for suggestions.
+pub(crate) fn names() -> impl Iterator<Item = &'static str> {
+    DENOMINATIONS
+        .iter()
+        .flat_map(|d| [d.name].into_iter().chain(d.aliases.iter().copied()))
+}
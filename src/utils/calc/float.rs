@@ -0,0 +1,288 @@
+use crate::{Error, Result};
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
+use regex::Regex;
+
+use super::{
+    evaluate_number,
+    number::Number,
+    parser::{tokenize, Parser, Token},
+};
+use crate::utils::{
+    format::{format_number, format_rational, FormatOptions},
+    locale::Locale,
+};
+
+/// IEEE-754 binary floating point format.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FloatFormat {
+    F32,
+    F64,
+}
+
+impl FloatFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "f32" | "float" | "single" => Some(FloatFormat::F32),
+            "f64" | "double" => Some(FloatFormat::F64),
+            _ => None,
+        }
+    }
+
+    /// Returns widths of the exponent and mantissa fields.
+    fn fields(&self) -> (u32, u32) {
+        match self {
+            FloatFormat::F32 => (8, 23),
+            FloatFormat::F64 => (11, 52),
+        }
+    }
+
+    fn bias(&self) -> i64 {
+        (1 << (self.fields().0 - 1)) - 1
+    }
+}
+
+/// What is printed first: the value, its bits or its bits in hex.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FloatView {
+    Value,
+    Bits,
+    Hex,
+}
+
+/// Bit pattern of a float with its fields, like `0.1 to f32 bits` shows it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FloatResult {
+    /// Raw bits, f32 uses the lower 32.
+    pub bits: u64,
+    pub format: FloatFormat,
+    pub view: FloatView,
+}
+
+impl FloatResult {
+    /// Rounds value to the nearest representable float of the format.
+    pub fn from_f64(value: f64, format: FloatFormat, view: FloatView) -> Self {
+        let bits = match format {
+            FloatFormat::F32 => (value as f32).to_bits() as u64,
+            FloatFormat::F64 => value.to_bits(),
+        };
+        Self { bits, format, view }
+    }
+
+    /// Returns the stored value, f32 is widened exactly.
+    pub fn value(&self) -> f64 {
+        match self.format {
+            FloatFormat::F32 => f32::from_bits(self.bits as u32) as f64,
+            FloatFormat::F64 => f64::from_bits(self.bits),
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        let (exp_bits, mantissa_bits) = self.format.fields();
+        self.bits >> (exp_bits + mantissa_bits) == 1
+    }
+
+    /// Returns raw exponent field.
+    pub fn biased_exponent(&self) -> u64 {
+        let (exp_bits, mantissa_bits) = self.format.fields();
+        (self.bits >> mantissa_bits) & ((1 << exp_bits) - 1)
+    }
+
+    /// Returns power of two of the value, subnormals have the minimal exponent and zeros have `0`.
+    pub fn exponent(&self) -> i64 {
+        if self.biased_exponent() == 0 && self.mantissa() == 0 {
+            return 0;
+        }
+        (self.biased_exponent() as i64).max(1) - self.format.bias()
+    }
+
+    /// Returns mantissa field without the implicit leading bit.
+    pub fn mantissa(&self) -> u64 {
+        self.bits & ((1 << self.format.fields().1) - 1)
+    }
+
+    pub fn is_subnormal(&self) -> bool {
+        self.biased_exponent() == 0 && self.mantissa() != 0
+    }
+
+    /// Returns distance from the value to the next representable float away from zero
+    /// (towards zero for the biggest finite float). Infinities and NaN give NaN.
+    pub fn ulp(&self) -> f64 {
+        let value = self.value().abs();
+        if !value.is_finite() {
+            return f64::NAN;
+        }
+        let abs_bits = self.bits & !(1 << (self.format.fields().0 + self.format.fields().1));
+        let neighbour = |bits: u64| Self { bits, ..*self }.value();
+        let next = neighbour(abs_bits + 1);
+        if next.is_finite() {
+            next - value
+        } else {
+            value - neighbour(abs_bits - 1)
+        }
+    }
+
+    /// Returns bits grouped as sign, exponent and mantissa, like `0 01111111 000…`.
+    pub fn bits_txt(&self) -> String {
+        let (exp_bits, mantissa_bits) = self.format.fields();
+        format!(
+            "{} {:0exp$b} {:0man$b}",
+            self.is_negative() as u8,
+            self.biased_exponent(),
+            self.mantissa(),
+            exp = exp_bits as usize,
+            man = mantissa_bits as usize
+        )
+    }
+
+    pub fn hex_txt(&self) -> String {
+        let digits = (self.format.fields().0 + self.format.fields().1 + 1) as usize / 4;
+        format!("0x{:0digits$x}", self.bits, digits = digits)
+    }
+
+    /// Returns exact decimal expansion of the stored value, negative zero is `-0`.
+    pub fn value_txt(&self) -> String {
+        if self.value() == 0. && self.is_negative() {
+            return "-0".to_string();
+        }
+        match BigRational::from_float(self.value()) {
+            Some(value) => format_rational(&value, &FormatOptions::default()),
+            None => format_number(self.value(), &FormatOptions::default()),
+        }
+    }
+
+    /// Returns String like `0x3dcccccd (sign +, exponent -4, mantissa 0x4ccccd, value 0.100000001490116119384765625, ULP 7.45058059692383e-9)`.
+    pub fn txt(&self) -> String {
+        let (head, value) = match self.view {
+            FloatView::Value => (self.value_txt(), None),
+            FloatView::Bits => (self.bits_txt(), Some(self.value_txt())),
+            FloatView::Hex => (self.hex_txt(), Some(self.value_txt())),
+        };
+        let mut details = vec![
+            format!("sign {}", if self.is_negative() { '-' } else { '+' }),
+            format!("exponent {}", self.exponent()),
+            format!("mantissa {:#x}", self.mantissa()),
+        ];
+        if self.is_subnormal() {
+            details.push("subnormal".to_string());
+        }
+        if let Some(value) = value {
+            details.push(format!("value {}", value));
+        }
+        if self.ulp().is_finite() {
+            details.push(format!(
+                "ULP {}",
+                format_number(self.ulp(), &FormatOptions::default())
+            ));
+        }
+        format!("{} ({})", head, details.join(", "))
+    }
+}
+
+/// Rounds number to the nearest float of the format, exact numbers are rounded only once.
+fn nearest(number: &Number, format: FloatFormat) -> Result<f64> {
+    Ok(match number {
+        Number::Exact(value) => round_exact(value, format),
+        Number::Float(value) => *value,
+        Number::Complex(_) => {
+            return Err(Error::Math(
+                "Complex numbers can't be stored in a float.".to_string(),
+            ))
+        }
+    })
+}
+
+/// Rounds exact number straight to the nearest float of the format, ties to even.
+fn round_exact(value: &BigRational, format: FloatFormat) -> f64 {
+    let (exp_bits, mantissa_bits) = format.fields();
+    let (numer, denom) = (value.numer().abs(), value.denom().clone());
+    if numer.is_zero() {
+        return 0.;
+    }
+    // Power of two of the value, `2^exponent <= value < 2^(exponent + 1)`.
+    let mut exponent = numer.bits() as i64 - denom.bits() as i64;
+    let below = match exponent {
+        e if e >= 0 => numer < &denom << e as usize,
+        e => (&numer << (-e) as usize) < denom,
+    };
+    if below {
+        exponent -= 1;
+    }
+    // Subnormals have the minimal exponent and fewer digits.
+    let min_exponent = 1 - format.bias();
+    let exponent = exponent.max(min_exponent);
+    let bits = if exponent > format.bias() {
+        u64::MAX
+    } else {
+        let shift = mantissa_bits as i64 - exponent;
+        let (numer, denom) = match shift {
+            s if s >= 0 => (numer << s as usize, denom),
+            s => (numer, denom << (-s) as usize),
+        };
+        let (mut mantissa, rest) = numer.div_rem(&denom);
+        let twice = rest * 2u32;
+        if twice > denom || (twice == denom && mantissa.is_odd()) {
+            mantissa += 1u32;
+        }
+        // The implicit bit adds to the exponent field, so a carry of rounding moves to the next power.
+        (((exponent - min_exponent) as u64) << mantissa_bits)
+            .saturating_add(mantissa.to_u64().unwrap_or(u64::MAX))
+    };
+    // Too big values are infinite.
+    let infinity = ((1 << exp_bits) - 1) << mantissa_bits;
+    let bits = bits.min(infinity) | ((value.is_negative() as u64) << (exp_bits + mantissa_bits));
+    match format {
+        FloatFormat::F32 => f32::from_bits(bits as u32) as f64,
+        FloatFormat::F64 => f64::from_bits(bits),
+    }
+}
+
+/// Inspects query like `0.1 to f32 bits`, `1.5 to f64 hex` or `0x3f800000 as f32`.
+/// An integer literal in other base is read as the bit pattern, anything else as the value to store.
+pub fn float_convert(query: &str) -> Result<FloatResult> {
+    float_convert_with(query, &Locale::default())
+}
+
+/// Same as `float_convert`, but numbers are read using separators of given locale.
+pub fn float_convert_with(query: &str, locale: &Locale) -> Result<FloatResult> {
+    let regex = Regex::new(
+        r"(?i)^(.+?)\s*(?:\bto\b|\bas\b|\bin\b|:)\s*(f32|f64|float|double|single)(?:\s+(bits|bin|hex))?\s*$",
+    )
+    .unwrap();
+    let Some(caps) = regex.captures(query) else {
+        return Err(Error::InvalidQuery(
+            "Invalid conversion query: error parsing parts.".to_string(),
+        ));
+    };
+    let format = FloatFormat::from_name(&caps[2]).unwrap();
+    let view = match caps.get(3).map(|m| m.as_str().to_lowercase()).as_deref() {
+        Some("hex") => FloatView::Hex,
+        Some(_) => FloatView::Bits,
+        None => FloatView::Value,
+    };
+    if let [Token::Based(bits)] = tokenize(&caps[1], locale)?.as_slice() {
+        let (exp_bits, mantissa_bits) = format.fields();
+        let Some(bits) = bits
+            .to_u64()
+            .filter(|b| b >> (exp_bits + mantissa_bits) <= 1)
+        else {
+            return Err(Error::Math(format!(
+                "The bit pattern is too wide for {:?}.",
+                format
+            )));
+        };
+        return Ok(FloatResult { bits, format, view });
+    }
+    // Exact numbers have no negative zero, so zero literals like `-0` are read as floats.
+    let text = caps[1].trim();
+    let number = match locale.parse_prefix(text) {
+        Some((value, len)) if value == 0. && len == text.len() => Number::Float(value),
+        _ => evaluate_number(&Parser::parse(&caps[1], locale)?)?,
+    };
+    Ok(FloatResult::from_f64(
+        nearest(&number, format)?,
+        format,
+        view,
+    ))
+}
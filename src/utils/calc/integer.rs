@@ -0,0 +1,286 @@
+use crate::{Error, Result};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use regex::Regex;
+use std::time::{Duration, Instant};
+
+use super::{
+    evaluate_number,
+    number::{Number, MAX_EXACT_OPERAND},
+    parser::Parser,
+    Value,
+};
+use crate::utils::locale::Locale;
+
+/// Names of combinatorics and number theory functions.
+/// `factor` gives several numbers, so it's a query of its own, see `factor_convert`.
+pub(super) const INTEGER_FUNCTIONS: [&str; 10] = [
+    "nCr", "ncr", "nPr", "npr", "gcd", "lcm", "mod", "isprime", "fib", "comb",
+];
+
+/// Bases of Miller-Rabin test, enough to be exact for numbers below 3.3e24.
+const WITNESSES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Biggest index of `fib`, its result has about 20 thousand digits.
+const MAX_FIB_INDEX: u64 = 100_000;
+
+/// Steps of Pollard's rho before giving up on a factor.
+const MAX_RHO_STEPS: u32 = 1_000_000;
+
+/// Time of Pollard's rho before giving up, factors of big numbers are asked while typing.
+const MAX_RHO_TIME: Duration = Duration::from_millis(500);
+
+pub(super) fn is_integer_function(name: &str) -> bool {
+    INTEGER_FUNCTIONS.contains(&name)
+}
+
+fn integers(name: &str, args: &[Number]) -> Result<Vec<BigInt>> {
+    match args
+        .iter()
+        .map(Number::to_integer)
+        .collect::<Option<Vec<_>>>()
+    {
+        Some(args) => Ok(args),
+        None => Err(Error::Math(format!(
+            "Invalid expression: '{}' is defined only for integers.",
+            name
+        ))),
+    }
+}
+
+/// Returns small non-negative integer argument, like `r` of `nCr`.
+fn small(name: &str, value: &BigInt) -> Result<u32> {
+    match value.to_u32().filter(|v| *v <= MAX_EXACT_OPERAND) {
+        Some(value) => Ok(value),
+        None => Err(Error::Math(format!(
+            "Invalid expression: argument of '{}' is out of range.",
+            name
+        ))),
+    }
+}
+
+/// Computes function like `nCr(10, 3)`, `gcd(12, 18, 30)`, `mod(-7, 3)` or `factor(360)`.
+/// `isprime` gives `1` or `0`, `factor` gives the list of prime factors.
+pub(super) fn integer_function(name: &str, args: Vec<Number>) -> Result<Value> {
+    if name == "mod" {
+        let [a, b] = args.as_slice() else {
+            return Err(Error::Math(
+                "Invalid expression: 'mod' takes two arguments.".to_string(),
+            ));
+        };
+        return Ok(Value::Number(a.modulo(b)?));
+    }
+    let args = integers(name, &args)?;
+    let result = match (name, args.as_slice()) {
+        ("nCr" | "ncr" | "comb", [n, r]) => {
+            if n.is_negative() || r.is_negative() {
+                return Err(Error::Math(format!(
+                    "Invalid expression: '{}' is defined only for non-negative integers.",
+                    name
+                )));
+            }
+            if r > n {
+                BigInt::zero()
+            } else {
+                let k = small(name, &r.min(&(n - r)).clone())?;
+                (0..k).fold(BigInt::one(), |acc, i| acc * (n - i) / (i + 1))
+            }
+        }
+        ("nPr" | "npr", [n, r]) => {
+            if n.is_negative() || r.is_negative() {
+                return Err(Error::Math(format!(
+                    "Invalid expression: '{}' is defined only for non-negative integers.",
+                    name
+                )));
+            }
+            if r > n {
+                BigInt::zero()
+            } else {
+                (0..small(name, r)?).fold(BigInt::one(), |acc, i| acc * (n - i))
+            }
+        }
+        ("gcd", [first, rest @ ..]) => rest.iter().fold(first.abs(), |acc, x| acc.gcd(x)),
+        ("lcm", [first, rest @ ..]) => rest.iter().fold(first.abs(), |acc, x| acc.lcm(x)),
+        ("isprime", [n]) => BigInt::from(is_prime(n) as u8),
+        ("fib", [n]) => {
+            let Some(index) = n.abs().to_u64().filter(|n| *n <= MAX_FIB_INDEX) else {
+                return Err(Error::Math(
+                    "Invalid expression: argument of 'fib' is out of range.".to_string(),
+                ));
+            };
+            let (fib, _) = fibonacci(index);
+            // F(-n) = (-1)^(n+1) F(n)
+            if n.is_negative() && n.is_even() {
+                -fib
+            } else {
+                fib
+            }
+        }
+        _ => {
+            return Err(Error::Math(format!(
+                "Invalid expression: wrong number of arguments of '{}'.",
+                name
+            )))
+        }
+    };
+    Ok(Value::Number(Number::from_bigint(result)))
+}
+
+/// Returns `(F(n), F(n + 1))` by fast doubling.
+fn fibonacci(n: u64) -> (BigInt, BigInt) {
+    if n == 0 {
+        return (BigInt::zero(), BigInt::one());
+    }
+    let (a, b) = fibonacci(n / 2);
+    let c = &a * (&b * 2 - &a);
+    let d = &a * &a + &b * &b;
+    if n.is_multiple_of(2) {
+        (c, d)
+    } else {
+        let e = &c + &d;
+        (d, e)
+    }
+}
+
+/// Miller-Rabin test, exact below 3.3e24 and a strong probable prime test above.
+pub fn is_prime(n: &BigInt) -> bool {
+    if n < &BigInt::from(2) {
+        return false;
+    }
+    for p in WITNESSES {
+        if n % p == BigInt::zero() {
+            return n == &BigInt::from(p);
+        }
+    }
+    let n_minus_one = n - 1u32;
+    let shift = n_minus_one.trailing_zeros().unwrap_or(0);
+    let odd = &n_minus_one >> shift;
+    WITNESSES.iter().all(|a| {
+        let mut x = BigInt::from(*a).modpow(&odd, n);
+        if x.is_one() || x == n_minus_one {
+            return true;
+        }
+        for _ in 1..shift {
+            x = x.modpow(&BigInt::from(2), n);
+            if x == n_minus_one {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Returns prime factors with their powers in ascending order, `360` is `2^3 × 3^2 × 5`.
+/// Negative numbers start with `-1`, `-12` is `-1 × 2^2 × 3`.
+pub fn factorize(n: &BigInt) -> Result<Vec<(BigInt, u32)>> {
+    let mut primes = Vec::new();
+    if n.is_negative() {
+        primes.push(-BigInt::one());
+    }
+    let mut n = n.abs();
+    if n.is_zero() {
+        return Err(Error::Math("Zero has no prime factorization.".to_string()));
+    }
+    let mut p = 2u32;
+    while p < 10_000 && BigInt::from(p * p) <= n {
+        while (&n % p).is_zero() {
+            primes.push(BigInt::from(p));
+            n /= p;
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    let mut stack = vec![n];
+    while let Some(n) = stack.pop() {
+        if n.is_one() {
+            continue;
+        }
+        if is_prime(&n) {
+            primes.push(n);
+            continue;
+        }
+        let Some(divisor) = rho(&n) else {
+            return Err(Error::Math("The number is too big to factor.".to_string()));
+        };
+        stack.push(&n / &divisor);
+        stack.push(divisor);
+    }
+    primes.sort();
+    let mut factors: Vec<(BigInt, u32)> = Vec::new();
+    for prime in primes {
+        match factors.last_mut() {
+            Some((last, count)) if *last == prime => *count += 1,
+            _ => factors.push((prime, 1)),
+        }
+    }
+    Ok(factors)
+}
+
+/// Finds a non-trivial divisor of composite number with Pollard's rho.
+fn rho(n: &BigInt) -> Option<BigInt> {
+    let start = Instant::now();
+    for c in 1u32..20 {
+        let step = |x: &BigInt| (x * x + c) % n;
+        let (mut x, mut y) = (BigInt::from(2), BigInt::from(2));
+        for _ in 0..MAX_RHO_STEPS / 20 {
+            x = step(&x);
+            y = step(&step(&y));
+            let d = (&x - &y).abs().gcd(n);
+            if start.elapsed() > MAX_RHO_TIME {
+                return None;
+            }
+            if d == *n {
+                break;
+            }
+            if !d.is_one() {
+                return Some(d);
+            }
+        }
+    }
+    None
+}
+
+/// Prime factorization of an integer.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Factorization {
+    /// Primes with their powers in ascending order.
+    pub factors: Vec<(BigInt, u32)>,
+}
+
+impl Factorization {
+    /// Returns String like `2^3 × 3^2 × 5`.
+    pub fn txt(&self) -> String {
+        self.factors
+            .iter()
+            .map(|(p, k)| match k {
+                1 => p.to_string(),
+                k => format!("{}^{}", p, k),
+            })
+            .collect::<Vec<_>>()
+            .join(" × ")
+    }
+}
+
+/// Factorizes query like `factor(360)` or `factor 2^64 - 1`.
+pub fn factor_convert(query: &str) -> Result<Factorization> {
+    factor_convert_with(query, &Locale::default())
+}
+
+/// Same as `factor_convert`, but numbers are read using separators of given locale.
+pub fn factor_convert_with(query: &str, locale: &Locale) -> Result<Factorization> {
+    let regex = Regex::new(r"(?i)^\s*factor\s*(.+?)\s*$").unwrap();
+    let Some(caps) = regex.captures(query) else {
+        return Err(Error::InvalidQuery(
+            "Invalid query: expected 'factor(n)'.".to_string(),
+        ));
+    };
+    let Some(n) = evaluate_number(&Parser::parse(&caps[1], locale)?)?.to_integer() else {
+        return Err(Error::Math("Only integers can be factorized.".to_string()));
+    };
+    if n.abs().is_one() {
+        return Err(Error::Math(format!("{} has no prime factors.", n)));
+    }
+    Ok(Factorization {
+        factors: factorize(&n)?,
+    })
+}
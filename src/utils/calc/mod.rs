@@ -0,0 +1,408 @@
+pub mod float;
+pub mod integer;
+mod money;
+pub mod number;
+mod parser;
+pub mod radix;
+pub mod solve;
+mod stats;
+
+use super::{
+    format::FormatOptions,
+    locale::Locale,
+    units::{Measurement, Unit},
+};
+use crate::{Error, Result};
+pub use float::{float_convert, float_convert_with, FloatFormat, FloatResult, FloatView};
+pub use integer::{factor_convert, factor_convert_with, factorize, is_prime, Factorization};
+pub use money::Money;
+use num_complex::Complex64;
+pub use number::{ComplexForm, Number};
+use parser::{Expr, Op, Parser};
+pub use radix::{radix_convert, radix_convert_with, IntWidth, Radix, RadixResult};
+use regex::Regex;
+pub use solve::{solve, solve_with, Solution};
+
+/// Intermediate value of the evaluation.
+#[derive(Debug, PartialEq, Clone)]
+enum Value {
+    Number(Number),
+    Measurement(Measurement),
+    Money(Money),
+    List(Vec<Value>),
+}
+
+/// Names of constants.
+const CONSTANTS: [&str; 7] = ["pi", "π", "tau", "τ", "e", "i", "j"];
+
+/// Names of functions of one number.
+const FUNCTIONS: [&str; 23] = [
+    "sqrt", "abs", "arg", "conj", "re", "im", "floor", "ceil", "round", "exp", "ln", "log", "lg",
+    "log2", "sin", "cos", "tan", "asin", "acos", "atan", "sinh", "cosh", "tanh",
+];
+
+fn constant(name: &str) -> Result<Number> {
+    Ok(match name {
+        "pi" | "π" => Number::Float(std::f64::consts::PI),
+        "tau" | "τ" => Number::Float(std::f64::consts::TAU),
+        "e" => Number::Float(std::f64::consts::E),
+        "i" | "j" => Number::imaginary_unit(),
+        _ => {
+            return Err(Error::Math(format!(
+                "Invalid expression: unknown identifier '{}'.",
+                name
+            )))
+        }
+    })
+}
+
+fn call(name: &str, args: Vec<Number>) -> Result<Number> {
+    let [x] = args.as_slice() else {
+        return Err(Error::Math(format!(
+            "Invalid expression: '{}' takes one argument.",
+            name
+        )));
+    };
+    Ok(match name {
+        "sqrt" => x.sqrt(),
+        "abs" => x.abs(),
+        "arg" => x.arg(),
+        "conj" => x.conj(),
+        "re" => x.re(),
+        "im" => x.im(),
+        "floor" => x.floor(),
+        "ceil" => x.ceil(),
+        "round" => x.round(),
+        "exp" => x.apply(f64::exp, Complex64::exp),
+        "ln" => x.apply(f64::ln, Complex64::ln),
+        "log" | "lg" => x.apply(f64::log10, |z| z.log(10.)),
+        "log2" => x.apply(f64::log2, |z| z.log(2.)),
+        "sin" => x.apply(f64::sin, Complex64::sin),
+        "cos" => x.apply(f64::cos, Complex64::cos),
+        "tan" => x.apply(f64::tan, Complex64::tan),
+        "asin" => x.apply(f64::asin, Complex64::asin),
+        "acos" => x.apply(f64::acos, Complex64::acos),
+        "atan" => x.apply(f64::atan, Complex64::atan),
+        "sinh" => x.apply(f64::sinh, Complex64::sinh),
+        "cosh" => x.apply(f64::cosh, Complex64::cosh),
+        "tanh" => x.apply(f64::tanh, Complex64::tanh),
+        _ => {
+            return Err(Error::Math(format!(
+                "Invalid expression: unknown function '{}'.",
+                name
+            )))
+        }
+    })
+}
+
+fn number(value: Value) -> Result<Number> {
+    match value {
+        Value::Number(n) => Ok(n),
+        Value::Measurement(_) => Err(Error::Math(
+            "Invalid expression: units are not allowed here.".to_string(),
+        )),
+        Value::Money(_) => Err(Error::Math(
+            "Invalid expression: money is not allowed here.".to_string(),
+        )),
+        Value::List(_) => Err(Error::Math(
+            "Invalid expression: lists are allowed only in functions.".to_string(),
+        )),
+    }
+}
+
+/// Returns real factor for a measurement.
+fn scalar(n: Number) -> Result<f64> {
+    if n.is_complex() {
+        return Err(Error::Math(
+            "Invalid expression: measurements can't be complex.".to_string(),
+        ));
+    }
+    Ok(n.to_f64())
+}
+
+fn scale(m: Measurement, factor: f64) -> Value {
+    Value::Measurement(Measurement::new(m.value * factor, m.unit))
+}
+
+/// Multiplies measurements in base units, like `12V * 2A = 24W`.
+fn product(a: Measurement, b: Measurement) -> Result<Value> {
+    let (a, b) = (a.to_base(), b.to_base());
+    let Some(unit) = a.unit.times(b.unit) else {
+        return Err(Error::IncompatibleUnits(format!(
+            "Cannot multiply {:?} by {:?}.",
+            a.unit, b.unit
+        )));
+    };
+    Ok(Value::Measurement(Measurement::new(
+        a.value * b.value,
+        unit,
+    )))
+}
+
+/// Divides measurements in base units, like `100m / 10s = 10m/s`. Same units give a number.
+fn quotient(a: Measurement, b: Measurement) -> Result<Value> {
+    let (a, b) = (a.to_base(), b.to_base());
+    if a.unit == b.unit {
+        return Ok(Value::Number(Number::Float(a.value / b.value)));
+    }
+    let Some(unit) = a.unit.per(b.unit) else {
+        return Err(Error::IncompatibleUnits(format!(
+            "Cannot divide {:?} by {:?}.",
+            a.unit, b.unit
+        )));
+    };
+    Ok(Value::Measurement(Measurement::new(
+        a.value / b.value,
+        unit,
+    )))
+}
+
+fn evaluate_expr(expr: &Expr) -> Result<Value> {
+    Ok(match expr {
+        Expr::Number(n) => Value::Number(n.clone()),
+        Expr::Quantity(n, unit) => Value::Measurement(Measurement::new(n.to_f64(), *unit)),
+        Expr::Currency(code) => Value::Money(Money::new(code, Number::from_int(1))),
+        Expr::Ident(name) => match Unit::from_symbol(name) {
+            Some(unit) => Value::Measurement(Measurement::new(1., unit)),
+            None => Value::Number(constant(name)?),
+        },
+        Expr::Neg(inner) => match evaluate_expr(inner)? {
+            Value::Number(n) => Value::Number(-n),
+            Value::Measurement(m) => Value::Measurement(Measurement::new(-m.value, m.unit)),
+            Value::Money(m) => Value::Money(m.neg()),
+            Value::List(_) => {
+                return Err(Error::Math(
+                    "Invalid expression: lists are allowed only in functions.".to_string(),
+                ))
+            }
+        },
+        Expr::Not(inner) => Value::Number(number(evaluate_expr(inner)?)?.not()?),
+        Expr::Factorial(inner) => Value::Number(number(evaluate_expr(inner)?)?.factorial()?),
+        Expr::List(items) => Value::List(items.iter().map(evaluate_expr).collect::<Result<_>>()?),
+        Expr::Call(name, args) if stats::is_statistic(name) => {
+            stats::statistic(name, args.iter().map(evaluate_expr).collect::<Result<_>>()?)?
+        }
+        Expr::Call(name, args) => {
+            let args = args
+                .iter()
+                .map(|arg| number(evaluate_expr(arg)?))
+                .collect::<Result<Vec<_>>>()?;
+            if integer::is_integer_function(name) {
+                integer::integer_function(name, args)?
+            } else {
+                Value::Number(call(name, args)?)
+            }
+        }
+        Expr::Binary(op, left, right) => match (evaluate_expr(left)?, evaluate_expr(right)?) {
+            (Value::Number(a), Value::Number(b)) => Value::Number(match op {
+                Op::Add => a + b,
+                Op::Sub => a - b,
+                Op::Mul => a * b,
+                Op::Div => (a / b)?,
+                Op::Pow => a.pow(&b)?,
+                Op::Angle => Number::from_polar(&a, &b),
+                Op::Mod => a.modulo(&b)?,
+                Op::Juxtapose => a * b,
+                Op::And => a.bitwise(&b, "&")?,
+                Op::Or => a.bitwise(&b, "|")?,
+                Op::Xor => a.bitwise(&b, "^")?,
+                Op::Shl => a.bitwise(&b, "<<")?,
+                Op::Shr => a.bitwise(&b, ">>")?,
+            }),
+            (Value::Measurement(a), Value::Measurement(b)) => match op {
+                Op::Add | Op::Juxtapose => Value::Measurement((a + b)?),
+                Op::Sub => Value::Measurement((a - b)?),
+                Op::Mul => product(a, b)?,
+                Op::Div => quotient(a, b)?,
+                _ => {
+                    return Err(Error::Math(
+                        "Invalid expression: measurements are not allowed here.".to_string(),
+                    ))
+                }
+            },
+            (Value::Money(a), Value::Money(b)) => match op {
+                Op::Add | Op::Juxtapose => Value::Money(a.add(b)),
+                Op::Sub => Value::Money(a.add(b.neg())),
+                Op::Div => Value::Number(a.ratio(&b)?),
+                _ => {
+                    return Err(Error::Math(
+                        "Invalid expression: money is not allowed here.".to_string(),
+                    ))
+                }
+            },
+            (Value::Number(n), Value::Money(m)) | (Value::Money(m), Value::Number(n))
+                if matches!(op, Op::Mul | Op::Juxtapose) =>
+            {
+                Value::Money(m.scale(&n)?)
+            }
+            (Value::Money(m), Value::Number(n)) if *op == Op::Div => Value::Money(m.divide(&n)?),
+            (Value::Measurement(mass), Value::Money(m))
+            | (Value::Money(m), Value::Measurement(mass))
+                if matches!(op, Op::Mul | Op::Juxtapose) =>
+            {
+                Value::Money(m.weigh(&mass)?)
+            }
+            (Value::Number(_), Value::Money(_)) | (Value::Money(_), Value::Number(_)) => {
+                return Err(Error::IncompatibleUnits(
+                    "Invalid expression: can't combine number and money.".to_string(),
+                ))
+            }
+            (Value::Number(n), Value::Measurement(m)) => match op {
+                Op::Mul | Op::Juxtapose => scale(m, scalar(n)?),
+                _ => {
+                    return Err(Error::IncompatibleUnits(
+                        "Invalid expression: can't combine number and measurement.".to_string(),
+                    ))
+                }
+            },
+            (Value::Measurement(m), Value::Number(n)) => match op {
+                Op::Mul | Op::Juxtapose => scale(m, scalar(n)?),
+                Op::Div => scale(m, 1. / scalar(n)?),
+                _ => {
+                    return Err(Error::IncompatibleUnits(
+                        "Invalid expression: can't combine number and measurement.".to_string(),
+                    ))
+                }
+            },
+            _ => {
+                return Err(Error::Math(
+                    "Invalid expression: error parsing unit part.".to_string(),
+                ))
+            }
+        },
+    })
+}
+
+fn evaluate_number(expr: &Expr) -> Result<Number> {
+    number(evaluate_expr(expr)?)
+}
+
+/// Evaluates a mathematical expression keeping big integers and decimals exact.
+/// Check `Number::is_exact` to know whether the result is approximate.
+pub fn evaluate(query: &str) -> Result<Number> {
+    evaluate_with(query, &Locale::default())
+}
+
+/// Same as `evaluate`, but numbers are read using separators of given locale.
+/// Function arguments are separated with `,` or `;` (use `;` if comma is the decimal separator).
+pub fn evaluate_with(query: &str, locale: &Locale) -> Result<Number> {
+    evaluate_number(&Parser::parse(query, locale)?)
+}
+
+/// Evaluates a mathematical expression and returns the result.
+pub fn eval(query: &str) -> Result<f64> {
+    eval_with(query, &Locale::default())
+}
+
+/// Same as `eval`, but numbers are read using separators of given locale.
+pub fn eval_with(query: &str, locale: &Locale) -> Result<f64> {
+    let result = evaluate_with(query, locale)?;
+    if result.is_complex() {
+        return Err(Error::Math("The result is a complex number.".to_string()));
+    }
+    Ok(result.to_f64())
+}
+
+/// Number printed in requested complex form.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ComplexResult {
+    pub value: Number,
+    pub form: ComplexForm,
+}
+
+impl ComplexResult {
+    /// Returns String formated number using given format options.
+    pub fn txt_with(&self, options: &FormatOptions) -> String {
+        self.value.txt_form(self.form, options)
+    }
+
+    pub fn txt(&self) -> String {
+        self.txt_with(&FormatOptions::default())
+    }
+}
+
+/// Evaluates query like `3+4i to polar` or `5∠53.13 to rect`.
+pub fn complex_convert(query: &str) -> Result<ComplexResult> {
+    complex_convert_with(query, &Locale::default())
+}
+
+/// Same as `complex_convert`, but numbers are read using separators of given locale.
+pub fn complex_convert_with(query: &str, locale: &Locale) -> Result<ComplexResult> {
+    let regex = Regex::new(
+        r"(?i)^(.+?)\s*(?:\bto\b|\bin\b|\bas\b|:)\s*(polar|rect|rectangular|cartesian)\s*$",
+    )
+    .unwrap();
+    let Some(caps) = regex.captures(query) else {
+        return Err(Error::InvalidQuery(
+            "Invalid conversion query: error parsing parts.".to_string(),
+        ));
+    };
+    let form = match caps[2].to_lowercase().as_str() {
+        "polar" => ComplexForm::Polar,
+        _ => ComplexForm::Rectangular,
+    };
+    Ok(ComplexResult {
+        value: evaluate_with(&caps[1], locale)?,
+        form,
+    })
+}
+
+/// Evaluates a mathematical expression with Unit and returns the result.
+pub fn meval(query: &str) -> Result<Measurement> {
+    meval_with(query, &Locale::default())
+}
+
+/// Same as `meval`, but numbers are read using separators of given locale.
+/// The result can be converted to a unit, like `(5km + 300m) * 2 to mi` or `12V * 2A in kW`.
+pub fn meval_with(query: &str, locale: &Locale) -> Result<Measurement> {
+    let regex = Regex::new(r"^(.+)(?:\bto\b|\bin\b|\bas\b|:)(.+)$").unwrap();
+    if let Some((caps, target)) = regex
+        .captures(query)
+        .and_then(|caps| target_unit(&caps[2], locale).map(|target| (caps, target)))
+    {
+        let result = measurement(evaluate_expr(&Parser::parse(&caps[1], locale)?)?)?;
+        if result.to_base().unit != Measurement::new(0., target).to_base().unit {
+            return Err(Error::IncompatibleUnits(
+                "Cannot convert measurements with different units.".to_string(),
+            ));
+        }
+        return Ok(result.to_other(target));
+    }
+    measurement(evaluate_expr(&Parser::parse(query, locale)?)?)
+}
+
+/// Evaluates an expression with amounts of money like `5 usd + 3 eur` or `20% of $300`.
+/// Amounts in different currencies are kept apart, `Converter::evaluate` converts them.
+pub fn money_eval(query: &str) -> Result<Money> {
+    money_eval_with(query, &Locale::default())
+}
+
+/// Same as `money_eval`, but numbers are read using separators of given locale.
+pub fn money_eval_with(query: &str, locale: &Locale) -> Result<Money> {
+    match evaluate_expr(&Parser::parse_money(query, locale)?)? {
+        Value::Money(money) => Ok(money),
+        _ => Err(Error::Math(
+            "Invalid expression: the result is not an amount of money.".to_string(),
+        )),
+    }
+}
+
+/// Returns unit written by its symbol like `km/h`, or as a product of units like `V*A`.
+fn target_unit(text: &str, locale: &Locale) -> Option<Unit> {
+    if let Some(unit) = Unit::from_symbol(text.trim()) {
+        return Some(unit);
+    }
+    match evaluate_expr(&Parser::parse(text, locale).ok()?).ok()? {
+        Value::Measurement(m) if m.value == 1. => Some(m.unit),
+        _ => None,
+    }
+}
+
+fn measurement(value: Value) -> Result<Measurement> {
+    match value {
+        Value::Measurement(m) => Ok(m),
+        _ => Err(Error::Math(
+            "Invalid expression: error parsing unit part.".to_string(),
+        )),
+    }
+}
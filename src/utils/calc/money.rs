@@ -0,0 +1,111 @@
+use crate::{Error, Result};
+
+use super::number::Number;
+use crate::utils::{
+    assets::{troy_ounces, METALS},
+    units::Measurement,
+};
+
+/// Amount of money as a sum of amounts in different currencies, like `5 usd + 3 eur`.
+/// The sum is kept per currency, so it's converted with rates only once, by `Converter::evaluate`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Money {
+    amounts: Vec<(&'static str, Number)>,
+}
+
+impl Money {
+    /// Amount in one currency, given by its code.
+    pub fn new(code: &'static str, amount: Number) -> Self {
+        Self {
+            amounts: vec![(code, amount)],
+        }
+    }
+
+    /// Returns amounts by currency codes, in the order currencies appear in the expression.
+    pub fn amounts(&self) -> &[(&'static str, Number)] {
+        &self.amounts
+    }
+
+    /// Returns the currency if the whole sum is in one currency.
+    pub fn currency(&self) -> Option<&'static str> {
+        let (first, _) = self.amounts.first()?;
+        self.amounts
+            .iter()
+            .all(|(code, _)| code == first)
+            .then_some(*first)
+    }
+
+    pub(super) fn add(mut self, other: Money) -> Money {
+        for (code, amount) in other.amounts {
+            match self.amounts.iter_mut().find(|(c, _)| *c == code) {
+                Some((_, sum)) => *sum = sum.clone() + amount,
+                None => self.amounts.push((code, amount)),
+            }
+        }
+        self
+    }
+
+    pub(super) fn neg(mut self) -> Money {
+        for (_, amount) in self.amounts.iter_mut() {
+            *amount = -amount.clone();
+        }
+        self
+    }
+
+    /// Multiplies all amounts by a real number, like in `20% of 300 eur`.
+    pub(super) fn scale(self, factor: &Number) -> Result<Money> {
+        real(factor)?;
+        self.map(|amount| Ok(amount * factor.clone()))
+    }
+
+    /// Multiplies price of a troy ounce of metal by the mass, like in `10 g gold`.
+    pub(super) fn weigh(self, mass: &Measurement) -> Result<Money> {
+        match (self.currency(), troy_ounces(mass)) {
+            (Some(code), Some(ounces)) if METALS.contains(&code) => {
+                self.scale(&Number::Float(ounces))
+            }
+            _ => Err(Error::IncompatibleUnits(
+                "Invalid expression: only metals like gold can be given by weight.".to_string(),
+            )),
+        }
+    }
+
+    pub(super) fn divide(self, divisor: &Number) -> Result<Money> {
+        real(divisor)?;
+        self.map(|amount| amount / divisor.clone())
+    }
+
+    /// Divides sums in the same currency, like `1200 usd / 100 usd`.
+    pub(super) fn ratio(&self, other: &Money) -> Result<Number> {
+        match (self.currency(), other.currency()) {
+            (Some(a), Some(b)) if a == b => sum(&self.amounts) / sum(&other.amounts),
+            _ => Err(Error::IncompatibleUnits(
+                "Cannot divide amounts in different currencies.".to_string(),
+            )),
+        }
+    }
+
+    fn map(self, f: impl Fn(Number) -> Result<Number>) -> Result<Money> {
+        let amounts = self
+            .amounts
+            .into_iter()
+            .map(|(code, amount)| Ok((code, f(amount)?)))
+            .collect::<Result<_>>()?;
+        Ok(Money { amounts })
+    }
+}
+
+fn sum(amounts: &[(&'static str, Number)]) -> Number {
+    amounts
+        .iter()
+        .fold(Number::from_int(0), |a, (_, b)| a + b.clone())
+}
+
+fn real(n: &Number) -> Result<()> {
+    if n.is_complex() {
+        return Err(Error::Math(
+            "Invalid expression: money can't be complex.".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,467 @@
+use std::{
+    cmp::Ordering,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use crate::{Error, Result};
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::utils::{
+    exact::parse_rational,
+    format::{format_number, format_rational, FormatOptions},
+};
+
+/// Biggest factorial and integer power exponent computed exactly.
+pub(super) const MAX_EXACT_OPERAND: u32 = 10_000;
+
+/// Biggest size in bits of an exact power, bigger powers like `(2^10000)^10000` are floats.
+const MAX_EXACT_BITS: u64 = 200_000;
+
+/// Parts of a complex number smaller than this share of its magnitude are printed as zero,
+/// so `exp(i*pi)` is `-1`, not `-1 + 1.2246e-16i`.
+const COMPLEX_EPSILON: f64 = 1e-12;
+
+/// Number of the calculator: exact big integers and decimals, or floats when exactness is lost
+/// (transcendental functions, irrational constants), or complex numbers.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Number {
+    /// Exact integer, decimal or fraction.
+    Exact(BigRational),
+    /// Approximate floating point value.
+    Float(f64),
+    /// Complex number with non-zero imaginary part.
+    Complex(Complex64),
+}
+
+/// How complex numbers are printed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ComplexForm {
+    /// `3 + 4i`
+    #[default]
+    Rectangular,
+    /// `5 ∠ 53.13°`
+    Polar,
+}
+
+impl Number {
+    /// Parses normalized decimal like `-12.5e3` exactly, numbers with huge exponents
+    /// like `1e10000000` are parsed as floats.
+    pub fn parse(number: &str) -> Option<Self> {
+        parse_rational(number)
+            .map(Number::Exact)
+            .or_else(|| number.parse().ok().map(Number::Float))
+    }
+
+    pub fn from_int(value: i64) -> Self {
+        Number::Exact(BigRational::from_integer(BigInt::from(value)))
+    }
+
+    pub fn from_bigint(value: BigInt) -> Self {
+        Number::Exact(BigRational::from_integer(value))
+    }
+
+    /// Makes number from complex value, dropping zero imaginary part.
+    pub fn complex(value: Complex64) -> Self {
+        if value.im == 0. {
+            Number::Float(value.re)
+        } else {
+            Number::Complex(value)
+        }
+    }
+
+    /// Imaginary unit `i`.
+    pub fn imaginary_unit() -> Self {
+        Number::Complex(Complex64::i())
+    }
+
+    /// Makes complex number from magnitude and angle in degrees.
+    pub fn from_polar(magnitude: &Number, degrees: &Number) -> Self {
+        Number::complex(Complex64::from_polar(
+            magnitude.to_f64(),
+            degrees.to_f64().to_radians(),
+        ))
+    }
+
+    pub fn to_complex(&self) -> Complex64 {
+        match self {
+            Number::Complex(value) => *value,
+            n => Complex64::new(n.to_f64(), 0.),
+        }
+    }
+
+    pub fn is_complex(&self) -> bool {
+        matches!(self, Number::Complex(_))
+    }
+
+    /// Returns is the result exact, not an approximation.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Number::Exact(_))
+    }
+
+    /// Returns real value, complex numbers give NaN.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Exact(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Float(value) => *value,
+            Number::Complex(_) => f64::NAN,
+        }
+    }
+
+    /// Returns integer value if the number is an exact integer.
+    pub fn to_integer(&self) -> Option<BigInt> {
+        match self {
+            Number::Exact(value) if value.is_integer() => Some(value.to_integer()),
+            _ => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Exact(value) => value.is_zero(),
+            Number::Float(value) => *value == 0.,
+            Number::Complex(value) => value.is_zero(),
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Number::Exact(value) => value.is_negative(),
+            Number::Float(value) => *value < 0.,
+            Number::Complex(_) => false,
+        }
+    }
+
+    /// Returns String formated number using given format options.
+    pub fn txt_with(&self, options: &FormatOptions) -> String {
+        match self {
+            Number::Exact(value) => format_rational(value, options),
+            Number::Float(value) => format_number(*value, options),
+            Number::Complex(value) => {
+                let limit = value.norm() * COMPLEX_EPSILON;
+                let re = if value.re.abs() < limit { 0. } else { value.re };
+                let im = if value.im.abs() < limit { 0. } else { value.im };
+                if im == 0. {
+                    return format_number(re, options);
+                }
+                let imaginary = match format_number(im.abs(), options).as_str() {
+                    "1" => "i".to_string(),
+                    im => format!("{}i", im),
+                };
+                match (re == 0., im < 0.) {
+                    (true, true) => format!("-{}", imaginary),
+                    (true, false) => imaginary,
+                    (false, negative) => format!(
+                        "{} {} {}",
+                        format_number(re, options),
+                        if negative { '-' } else { '+' },
+                        imaginary
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Returns String formated number in given complex form.
+    pub fn txt_form(&self, form: ComplexForm, options: &FormatOptions) -> String {
+        match form {
+            ComplexForm::Rectangular => self.txt_with(options),
+            ComplexForm::Polar => {
+                let value = self.to_complex();
+                let (magnitude, angle) = value.to_polar();
+                format!(
+                    "{} ∠ {}°",
+                    format_number(magnitude, options),
+                    format_number(angle.to_degrees(), options)
+                )
+            }
+        }
+    }
+
+    pub fn txt(&self) -> String {
+        self.txt_with(&FormatOptions::default())
+    }
+
+    /// Applies float function, keeping exactness when `exact` gives a result.
+    fn map_float(
+        &self,
+        exact: impl Fn(&BigRational) -> Option<BigRational>,
+        f: fn(f64) -> f64,
+    ) -> Number {
+        if let Number::Exact(value) = self {
+            if let Some(result) = exact(value) {
+                return Number::Exact(result);
+            }
+        }
+        Number::Float(f(self.to_f64()))
+    }
+
+    pub fn pow(&self, exponent: &Number) -> Result<Number> {
+        if self.is_complex() || exponent.is_complex() {
+            return Ok(Number::complex(
+                self.to_complex().powc(exponent.to_complex()),
+            ));
+        }
+        if let (Number::Exact(base), Some(exp)) = (self, exponent.to_integer()) {
+            let bits = base.numer().bits() + base.denom().bits();
+            if let Some(exp) = exp
+                .to_i64()
+                .filter(|e| e.unsigned_abs() <= MAX_EXACT_OPERAND as u64)
+                .filter(|e| bits * e.unsigned_abs() <= MAX_EXACT_BITS)
+            {
+                if base.is_zero() && exp < 0 {
+                    return Err(Error::Math("Division by zero.".to_string()));
+                }
+                return Ok(Number::Exact(num_traits::pow::Pow::pow(base, exp as i32)));
+            }
+        }
+        // Exact roots like `4^0.5` or `8^(1/3)`.
+        // Integer exponents above the limit are not roots, they are computed as floats.
+        if let (Number::Exact(base), Number::Exact(exp)) = (self, exponent) {
+            let root = exp.denom().to_u32().filter(|n| *n != 1);
+            if let Some(root) = root.and_then(|n| exact_root(base, n)) {
+                return Number::Exact(root).pow(&Number::Exact(BigRational::from_integer(
+                    exp.numer().clone(),
+                )));
+            }
+        }
+        let result = self.to_f64().powf(exponent.to_f64());
+        if result.is_nan() && self.is_negative() {
+            // Fractional power of negative number, like `(-8)^0.5`.
+            return Ok(Number::complex(
+                self.to_complex().powc(exponent.to_complex()),
+            ));
+        }
+        Ok(Number::Float(result))
+    }
+
+    /// Bitwise not of an integer (two's complement, so `~x == -x - 1`).
+    pub fn not(&self) -> Result<Number> {
+        let Some(value) = self.to_integer() else {
+            return Err(Error::Math(
+                "Bitwise operations are defined only for integers.".to_string(),
+            ));
+        };
+        Ok(Number::from_bigint(!value))
+    }
+
+    /// Bitwise operation (`&`, `|`, `^`, `<<` or `>>`) on integers
+    /// with two's complement semantics for negative values.
+    pub fn bitwise(&self, other: &Number, op: &str) -> Result<Number> {
+        let (Some(a), Some(b)) = (self.to_integer(), other.to_integer()) else {
+            return Err(Error::Math(
+                "Bitwise operations are defined only for integers.".to_string(),
+            ));
+        };
+        let shift = || match b.to_u32().filter(|b| *b <= MAX_EXACT_OPERAND) {
+            Some(shift) => Ok(shift),
+            None => Err(Error::Math("Invalid shift amount.".to_string())),
+        };
+        Ok(Number::from_bigint(match op {
+            "&" => a & b,
+            "|" => a | b,
+            "^" => a ^ b,
+            "<<" => a << shift()?,
+            ">>" => a >> shift()?,
+            _ => return Err(Error::Math(format!("Unknown bitwise operator '{}'.", op))),
+        }))
+    }
+
+    /// Remainder with the sign of the divisor, like `-7 mod 3 = 2`.
+    pub fn modulo(&self, other: &Number) -> Result<Number> {
+        if self.is_complex() || other.is_complex() {
+            return Err(Error::Math(
+                "Modulo is defined only for real numbers.".to_string(),
+            ));
+        }
+        let quotient = (self.clone() / other.clone())?.floor();
+        Ok(self.clone() - other.clone() * quotient)
+    }
+
+    pub fn factorial(&self) -> Result<Number> {
+        let Some(n) = self.to_integer().and_then(|n| n.to_u32()) else {
+            return Err(Error::Math(
+                "Factorial is defined only for non-negative integers.".to_string(),
+            ));
+        };
+        if n > MAX_EXACT_OPERAND {
+            return Err(Error::Math("Factorial argument is too big.".to_string()));
+        }
+        let result = (2..=n).fold(BigInt::one(), |acc, i| acc * i);
+        Ok(Number::Exact(BigRational::from_integer(result)))
+    }
+
+    pub fn sqrt(&self) -> Number {
+        match self {
+            Number::Exact(value) if value.is_negative() => match exact_root(&-value, 2) {
+                Some(root) => {
+                    Number::Complex(Complex64::new(0., root.to_f64().unwrap_or(f64::NAN)))
+                }
+                None => self.apply(f64::sqrt, Complex64::sqrt),
+            },
+            Number::Exact(value) => match exact_root(value, 2) {
+                Some(root) => Number::Exact(root),
+                None => self.apply(f64::sqrt, Complex64::sqrt),
+            },
+            _ => self.apply(f64::sqrt, Complex64::sqrt),
+        }
+    }
+
+    /// Absolute value, or magnitude of complex number.
+    pub fn abs(&self) -> Number {
+        match self {
+            Number::Exact(value) => Number::Exact(value.abs()),
+            Number::Float(value) => Number::Float(value.abs()),
+            Number::Complex(value) => Number::Float(value.norm()),
+        }
+    }
+
+    /// Angle of the number on the complex plane in radians.
+    pub fn arg(&self) -> Number {
+        Number::Float(self.to_complex().arg())
+    }
+
+    /// Complex conjugate.
+    pub fn conj(&self) -> Number {
+        match self {
+            Number::Complex(value) => Number::Complex(value.conj()),
+            n => n.clone(),
+        }
+    }
+
+    /// Real part.
+    pub fn re(&self) -> Number {
+        match self {
+            Number::Complex(value) => Number::Float(value.re),
+            n => n.clone(),
+        }
+    }
+
+    /// Imaginary part.
+    pub fn im(&self) -> Number {
+        match self {
+            Number::Complex(value) => Number::Float(value.im),
+            _ => Number::from_int(0),
+        }
+    }
+
+    pub fn floor(&self) -> Number {
+        self.map_float(|v| Some(v.floor()), f64::floor)
+    }
+
+    pub fn ceil(&self) -> Number {
+        self.map_float(|v| Some(v.ceil()), f64::ceil)
+    }
+
+    pub fn round(&self) -> Number {
+        self.map_float(|v| Some(v.round()), f64::round)
+    }
+
+    /// Applies transcendental function, the result is approximate.
+    /// Complex version is used for complex numbers and outside of the real domain (`ln(-1)`, `asin(2)`).
+    pub fn apply(&self, real: fn(f64) -> f64, complex: fn(Complex64) -> Complex64) -> Number {
+        if !self.is_complex() {
+            let result = real(self.to_f64());
+            if !result.is_nan() || self.to_f64().is_nan() {
+                return Number::Float(result);
+            }
+        }
+        Number::complex(complex(self.to_complex()))
+    }
+}
+
+/// Returns exact n-th root of non-negative rational if numerator and denominator are perfect powers.
+fn exact_root(value: &BigRational, n: u32) -> Option<BigRational> {
+    if n == 0 || (value.is_negative() && n.is_multiple_of(2)) {
+        return None;
+    }
+    let root = |x: &BigInt| -> Option<BigInt> {
+        let r = x.nth_root(n);
+        (num_traits::pow::Pow::pow(&r, n) == *x).then_some(r)
+    };
+    Some(BigRational::new(root(value.numer())?, root(value.denom())?))
+}
+
+impl Add for Number {
+    type Output = Number;
+
+    fn add(self, other: Self) -> Number {
+        match (self, other) {
+            (Number::Exact(a), Number::Exact(b)) => Number::Exact(a + b),
+            (a, b) if a.is_complex() || b.is_complex() => {
+                Number::complex(a.to_complex() + b.to_complex())
+            }
+            (a, b) => Number::Float(a.to_f64() + b.to_f64()),
+        }
+    }
+}
+
+impl Sub for Number {
+    type Output = Number;
+
+    fn sub(self, other: Self) -> Number {
+        match (self, other) {
+            (Number::Exact(a), Number::Exact(b)) => Number::Exact(a - b),
+            (a, b) if a.is_complex() || b.is_complex() => {
+                Number::complex(a.to_complex() - b.to_complex())
+            }
+            (a, b) => Number::Float(a.to_f64() - b.to_f64()),
+        }
+    }
+}
+
+impl Mul for Number {
+    type Output = Number;
+
+    fn mul(self, other: Self) -> Number {
+        match (self, other) {
+            (Number::Exact(a), Number::Exact(b)) => Number::Exact(a * b),
+            (a, b) if a.is_complex() || b.is_complex() => {
+                Number::complex(a.to_complex() * b.to_complex())
+            }
+            (a, b) => Number::Float(a.to_f64() * b.to_f64()),
+        }
+    }
+}
+
+impl Div for Number {
+    type Output = Result<Number>;
+
+    fn div(self, other: Self) -> Result<Number> {
+        Ok(match (self, other) {
+            (Number::Exact(_), Number::Exact(b)) if b.is_zero() => {
+                return Err(Error::Math("Division by zero.".to_string()))
+            }
+            (Number::Exact(a), Number::Exact(b)) => Number::Exact(a / b),
+            (a, b) if a.is_complex() || b.is_complex() => {
+                Number::complex(a.to_complex() / b.to_complex())
+            }
+            (a, b) => Number::Float(a.to_f64() / b.to_f64()),
+        })
+    }
+}
+
+impl Neg for Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Exact(value) => Number::Exact(-value),
+            Number::Float(value) => Number::Float(-value),
+            Number::Complex(value) => Number::Complex(-value),
+        }
+    }
+}
+
+/// Real numbers are ordered, complex numbers are not comparable.
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Exact(a), Number::Exact(b)) => a.partial_cmp(b),
+            (a, b) if a.is_complex() || b.is_complex() => None,
+            (a, b) => a.to_f64().partial_cmp(&b.to_f64()),
+        }
+    }
+}
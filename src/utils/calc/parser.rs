@@ -0,0 +1,590 @@
+use crate::Result;
+use num_bigint::BigInt;
+use num_traits::Num;
+
+use std::ops::Range;
+
+use super::{
+    integer::{is_integer_function, INTEGER_FUNCTIONS},
+    number::Number,
+    stats::{is_statistic, STATISTICS},
+    CONSTANTS, FUNCTIONS,
+};
+use crate::utils::{assets, diagnostic::ParseError, iso4217, locale::Locale, units::Unit};
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum Token {
+    /// Normalized decimal number like `-1234.5e-3`.
+    Number(String),
+    /// Integer written in other base, like `0xff`.
+    Based(BigInt),
+    Ident(String),
+    Op(char),
+    Comma,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    /// Polar form `magnitude ∠ degrees`.
+    Angle,
+    /// Remainder with the sign of the divisor.
+    Mod,
+    /// Implicit multiplication like `2pi`, or sum of measurements like `3 m 20 cm`.
+    Juxtapose,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+}
+
+/// Parsed expression.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum Expr {
+    Number(Number),
+    Quantity(Number, Unit),
+    /// One unit of a currency, given by its code.
+    Currency(&'static str),
+    /// Constant like `pi`.
+    Ident(String),
+    Neg(Box<Expr>),
+    /// Bitwise not.
+    Not(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Factorial(Box<Expr>),
+    Call(String, Vec<Expr>),
+    /// List literal like `[1, 2, 3]`.
+    List(Vec<Expr>),
+}
+
+/// Words used as operators, they don't start an implicitly multiplied operand.
+const KEYWORDS: [&str; 3] = ["xor", "mod", "of"];
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == 'µ' || c == '°'
+}
+
+/// Returns is the character a currency sign like `$` or `₴`, it's read as an identifier.
+fn is_currency_sign(c: char) -> bool {
+    !c.is_alphanumeric() && iso4217::lookup(c.encode_utf8(&mut [0; 4])).is_some()
+}
+
+pub(crate) fn tokenize(query: &str, locale: &Locale) -> Result<Vec<Token>> {
+    Ok(tokenize_spanned(query, locale)?.0)
+}
+
+/// Splits query to tokens, also returns byte range of every token.
+pub(crate) fn tokenize_spanned(
+    query: &str,
+    locale: &Locale,
+) -> Result<(Vec<Token>, Vec<Range<usize>>)> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut spans = Vec::new();
+    let mut rest = query;
+    // Open brackets, `true` for argument lists where `,` always separates values, so `sum(1,234)` is `235`.
+    let mut brackets = Vec::new();
+    let list_locale = Locale {
+        grouping: locale.grouping.filter(|g| *g != ','),
+        ..*locale
+    };
+    while let Some(c) = rest.chars().next() {
+        let start = query.len() - rest.len();
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let starts_number = c.is_ascii_digit()
+            || (c == locale.decimal
+                && rest[c.len_utf8()..].starts_with(|n: char| n.is_ascii_digit()));
+        let (token, len) = if let Some((value, len)) = based_literal(rest) {
+            (Token::Based(value), len)
+        } else if starts_number {
+            let locale = match brackets.last() {
+                Some(true) => &list_locale,
+                _ => locale,
+            };
+            let Some((number, len)) = locale.normalize_prefix(rest) else {
+                let error = ParseError::new(
+                    "Invalid expression: error parsing number.",
+                    start..start + 1,
+                )
+                .expected(&["number"]);
+                return Err(error.into());
+            };
+            // A second decimal part, like in `5.5.5`, is not another number.
+            let mut tail = rest[len..].chars();
+            if tail.next() == Some(locale.decimal)
+                && tail.next().is_some_and(|c| c.is_ascii_digit())
+            {
+                let end = rest[len..]
+                    .find(|c: char| !c.is_ascii_digit() && c != locale.decimal)
+                    .map_or(rest.len(), |end| len + end);
+                let error = ParseError::new(
+                    "Invalid expression: error parsing number.",
+                    start..start + end,
+                )
+                .expected(&["number"]);
+                return Err(error.into());
+            }
+            (Token::Number(number), len)
+        } else if is_ident_start(c) {
+            let len = rest
+                .find(|c: char| !(is_ident_start(c) || c.is_ascii_digit()))
+                .unwrap_or(rest.len());
+            (Token::Ident(rest[..len].to_string()), len)
+        } else if is_currency_sign(c) {
+            (Token::Ident(c.to_string()), c.len_utf8())
+        } else if rest.starts_with("**") || rest.starts_with("<<") || rest.starts_with(">>") {
+            // Two-character operators are two tokens, the parser reads them in pairs.
+            tokens.extend([Token::Op(c), Token::Op(c)]);
+            spans.extend([start..start + 1, start + 1..start + 2]);
+            rest = &rest[2..];
+            continue;
+        } else {
+            let token = match c {
+                '+' => Token::Op('+'),
+                '-' | '−' => Token::Op('-'),
+                '*' | '×' | '·' => Token::Op('*'),
+                '/' | '÷' => Token::Op('/'),
+                '^' => Token::Op('^'),
+                '∠' => Token::Op('∠'),
+                '!' => Token::Op('!'),
+                '%' => Token::Op('%'),
+                '&' | '|' | '~' => Token::Op(c),
+                ',' | ';' => Token::Comma,
+                '(' => {
+                    brackets.push(matches!(tokens.last(), Some(Token::Ident(_))));
+                    Token::LParen
+                }
+                '[' => {
+                    brackets.push(true);
+                    Token::LBracket
+                }
+                ')' | ']' => {
+                    brackets.pop();
+                    if c == ')' {
+                        Token::RParen
+                    } else {
+                        Token::RBracket
+                    }
+                }
+                _ => {
+                    let message = format!("Invalid expression: unexpected character '{}'.", c);
+                    let error = ParseError::new(&message, start..start + c.len_utf8());
+                    return Err(error.into());
+                }
+            };
+            (token, c.len_utf8())
+        };
+        tokens.push(token);
+        spans.push(start..start + len);
+        rest = &rest[len..];
+    }
+    Ok((tokens, spans))
+}
+
+/// Reads integer literal like `0xff`, `0b1010` or `0o17` (`_` can separate digits).
+fn based_literal(text: &str) -> Option<(BigInt, usize)> {
+    let radix = match text.get(..2)? {
+        "0x" | "0X" => 16,
+        "0b" | "0B" => 2,
+        "0o" | "0O" => 8,
+        _ => return None,
+    };
+    let len = text[2..]
+        .find(|c: char| !(c.is_digit(radix) || c == '_'))
+        .map(|len| len + 2)
+        .unwrap_or(text.len());
+    let digits = text[2..len].replace('_', "");
+    if digits.is_empty() {
+        return None;
+    }
+    Some((BigInt::from_str_radix(&digits, radix).ok()?, len))
+}
+
+/// Recursive descent parser.
+/// `expr = bitxor (| bitxor)*`, `bitxor = bitand (xor bitand)*`, `bitand = shift (& shift)*`,
+/// `shift = sum (<<|>> sum)*`, `sum = term (+|- term)*`, `term = implicit (*|/|∠|mod implicit)*`,
+/// `implicit = unary power*`, `unary = -unary | ~unary | power`, `power = postfix (^ unary)?`,
+/// `postfix = primary (!|%)*`.
+///
+/// `x of y` is `x * y`, so `20% of 300` is `60`. In money mode currency codes, signs and
+/// aliases are amounts of one unit of the currency, so `$5` and `5 usd` are `5 * usd`.
+///
+/// Implicit multiplication binds tighter than `*` and `/`, so `1/2pi` is `1/(2pi)`.
+/// Units written alone are measurements of one unit, so `2pi rad` is `2pi * 1 rad`.
+///
+/// In programmer mode `^` is bitwise xor and `**` is the power operator. The mode is on when
+/// the expression has integer literals in other bases or bitwise operators. Base conversions
+/// keep `^` as the power, so `2^8 to hex` is `0x100`, xor is written as `xor` there.
+pub(crate) struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    programmer: bool,
+    /// Unknown of an equation, it's never read as a unit (`t` is not a ton in `solve t: …`).
+    variable: Option<String>,
+    /// Byte ranges of the tokens in the query, for errors.
+    spans: Vec<Range<usize>>,
+    /// Length of the query, missing tokens are reported there.
+    end: usize,
+    /// Read currency names as money, like `usd` in `5 usd + 3 eur`.
+    money: bool,
+}
+
+impl Parser {
+    pub fn parse(query: &str, locale: &Locale) -> Result<Expr> {
+        Self::parse_full(query, locale, None, None, false)
+    }
+
+    /// Parses the number of a base conversion, `^` is the power there.
+    pub fn parse_conversion(query: &str, locale: &Locale) -> Result<Expr> {
+        Self::parse_full(query, locale, Some(false), None, false)
+    }
+
+    /// Parses expression with amounts of money.
+    pub fn parse_money(query: &str, locale: &Locale) -> Result<Expr> {
+        Self::parse_full(query, locale, None, None, true)
+    }
+
+    /// Parses expression with an unknown, which stays `Expr::Ident`.
+    pub fn parse_with_variable(query: &str, locale: &Locale, variable: &str) -> Result<Expr> {
+        Self::parse_full(query, locale, None, Some(variable.to_string()), false)
+    }
+
+    /// `programmer` is detected from the tokens when it's `None`.
+    fn parse_full(
+        query: &str,
+        locale: &Locale,
+        programmer: Option<bool>,
+        variable: Option<String>,
+        money: bool,
+    ) -> Result<Expr> {
+        let (tokens, spans) = tokenize_spanned(query, locale)?;
+        let programmer = programmer.unwrap_or_else(|| {
+            tokens.iter().any(|token| {
+                matches!(
+                    token,
+                    Token::Based(_) | Token::Op('&') | Token::Op('|') | Token::Op('~')
+                ) || matches!(token, Token::Op('<') | Token::Op('>'))
+            })
+        });
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            programmer,
+            variable,
+            spans,
+            end: query.trim_end().len(),
+            money,
+        };
+        let expr = parser.expr()?;
+        if parser.pos != parser.tokens.len() {
+            let error = match parser.peek() {
+                Some(Token::RParen | Token::RBracket) => {
+                    parser.error(parser.pos, "Invalid expression: unmatched closing bracket.")
+                }
+                _ => parser
+                    .error(parser.pos, "Invalid expression: unexpected token.")
+                    .expected(&["operator"]),
+            };
+            return Err(error.into());
+        }
+        Ok(expr)
+    }
+
+    /// Returns error at the token, or at the end of the query if there is no such token.
+    fn error(&self, pos: usize, message: &str) -> ParseError {
+        let span = match self.spans.get(pos) {
+            Some(span) => span.clone(),
+            None => self.end..self.end,
+        };
+        ParseError::new(message, span)
+    }
+
+    /// Checks that identifier is a constant, a unit or the unknown, suggests close names if not.
+    fn check_ident(&self, pos: usize, name: &str) -> Result<()> {
+        if CONSTANTS.contains(&name)
+            || Unit::from_symbol(name).is_some()
+            || self.variable.as_deref() == Some(name)
+        {
+            return Ok(());
+        }
+        let message = format!("Invalid expression: unknown identifier '{}'.", name);
+        let candidates = CONSTANTS.into_iter().chain(Unit::symbols());
+        let error = self.error(pos, &message);
+        let error = if self.money {
+            error
+                .expected(&["constant", "unit", "currency"])
+                .suggest(name, candidates.chain(iso4217::names()))
+        } else {
+            error
+                .expected(&["constant", "unit"])
+                .suggest(name, candidates)
+        };
+        Err(error.into())
+    }
+
+    /// Checks that function exists, suggests close names if not.
+    fn check_function(&self, pos: usize, name: &str) -> Result<()> {
+        if FUNCTIONS.contains(&name) || is_statistic(name) || is_integer_function(name) {
+            return Ok(());
+        }
+        let message = format!("Invalid expression: unknown function '{}'.", name);
+        let candidates = FUNCTIONS
+            .into_iter()
+            .chain(STATISTICS)
+            .chain(INTEGER_FUNCTIONS);
+        Err(self
+            .error(pos, &message)
+            .expected(&["function"])
+            .suggest(name, candidates)
+            .into())
+    }
+
+    /// Consumes the closing bracket or returns error pointing where it's missing.
+    fn close(&mut self, close: Token) -> Result<()> {
+        if self.peek() == Some(&close) {
+            self.pos += 1;
+            return Ok(());
+        }
+        let expected = match close {
+            Token::RBracket => "]",
+            _ => ")",
+        };
+        let message = format!("Invalid expression: missing '{}'.", expected);
+        Err(self.error(self.pos, &message).expected(&[expected]).into())
+    }
+
+    /// Returns one unit of the currency or sub-unit like `sat` named by identifier in money mode.
+    /// Constants are not currencies, so `e` stays Euler's number, and units are read as units
+    /// unless they are currency codes, so `g` in `10 g gold` is a gram, not the sign of gourde.
+    fn currency(&self, name: &str) -> Option<Expr> {
+        if !self.money || CONSTANTS.contains(&name) || KEYWORDS.contains(&name) {
+            return None;
+        }
+        if Unit::from_symbol(name).is_some() && iso4217::find(name).is_none() {
+            return None;
+        }
+        if let Some(sub_unit) = assets::denomination(name) {
+            let factor = Expr::Number(Number::parse(sub_unit.factor)?);
+            return Some(Self::binary(Op::Mul, factor, Expr::Currency(sub_unit.code)));
+        }
+        iso4217::lookup(name).map(|info| Expr::Currency(info.code))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn binary(op: Op, left: Expr, right: Expr) -> Expr {
+        Expr::Binary(op, Box::new(left), Box::new(right))
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let mut left = self.bitxor()?;
+        while let Some(Token::Op('|')) = self.peek() {
+            self.pos += 1;
+            left = Self::binary(Op::Or, left, self.bitxor()?);
+        }
+        Ok(left)
+    }
+
+    fn bitxor(&mut self) -> Result<Expr> {
+        let mut left = self.bitand()?;
+        loop {
+            match self.peek() {
+                Some(Token::Op('^')) if self.programmer => {}
+                Some(Token::Ident(name)) if name == "xor" => {}
+                _ => return Ok(left),
+            }
+            self.pos += 1;
+            left = Self::binary(Op::Xor, left, self.bitand()?);
+        }
+    }
+
+    fn bitand(&mut self) -> Result<Expr> {
+        let mut left = self.shift()?;
+        while let Some(Token::Op('&')) = self.peek() {
+            self.pos += 1;
+            left = Self::binary(Op::And, left, self.shift()?);
+        }
+        Ok(left)
+    }
+
+    fn shift(&mut self) -> Result<Expr> {
+        let mut left = self.sum()?;
+        loop {
+            let op = match (self.peek(), self.tokens.get(self.pos + 1)) {
+                (Some(Token::Op('<')), Some(Token::Op('<'))) => Op::Shl,
+                (Some(Token::Op('>')), Some(Token::Op('>'))) => Op::Shr,
+                _ => return Ok(left),
+            };
+            self.pos += 2;
+            left = Self::binary(op, left, self.sum()?);
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        let mut left = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op('+')) => Op::Add,
+                Some(Token::Op('-')) => Op::Sub,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            left = Self::binary(op, left, self.term()?);
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let mut left = self.implicit()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op('*')) => Op::Mul,
+                Some(Token::Op('/')) => Op::Div,
+                Some(Token::Op('∠')) => Op::Angle,
+                Some(Token::Ident(name)) if name == "mod" => Op::Mod,
+                Some(Token::Ident(name)) if name == "of" => Op::Mul,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            left = Self::binary(op, left, self.implicit()?);
+        }
+    }
+
+    fn implicit(&mut self) -> Result<Expr> {
+        let mut left = self.unary()?;
+        while match self.peek() {
+            Some(Token::Number(_) | Token::Based(_) | Token::LParen | Token::LBracket) => true,
+            Some(Token::Ident(name)) => !KEYWORDS.contains(&name.as_str()),
+            _ => false,
+        } {
+            left = Self::binary(Op::Juxtapose, left, self.power()?);
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.pos += 1;
+                Ok(Expr::Neg(Box::new(self.unary()?)))
+            }
+            Some(Token::Op('+')) => {
+                self.pos += 1;
+                self.unary()
+            }
+            Some(Token::Op('~')) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr> {
+        let base = self.postfix()?;
+        match (self.peek(), self.tokens.get(self.pos + 1)) {
+            (Some(Token::Op('^')), _) if !self.programmer => self.pos += 1,
+            (Some(Token::Op('*')), Some(Token::Op('*'))) => self.pos += 2,
+            _ => return Ok(base),
+        }
+        Ok(Self::binary(Op::Pow, base, self.unary()?))
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+        loop {
+            match self.peek() {
+                Some(Token::Op('!')) => expr = Expr::Factorial(Box::new(expr)),
+                Some(Token::Op('%')) => {
+                    let hundred = Expr::Number(Number::from_int(100));
+                    expr = Self::binary(Op::Div, expr, hundred);
+                }
+                _ => return Ok(expr),
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Number(number)) => {
+                let Some(number) = Number::parse(&number) else {
+                    let error =
+                        self.error(self.pos - 1, "Invalid expression: error parsing number.");
+                    return Err(error.into());
+                };
+                if let Some(Token::Ident(symbol)) = self.peek().cloned() {
+                    if symbol == "i" || symbol == "j" {
+                        self.pos += 1;
+                        return Ok(Expr::Number(number * Number::imaginary_unit()));
+                    }
+                    if let Some(unit) = Unit::from_symbol(&symbol)
+                        .filter(|_| self.variable.as_ref() != Some(&symbol))
+                        .filter(|_| self.currency(&symbol).is_none())
+                    {
+                        self.pos += 1;
+                        return Ok(Expr::Quantity(number, unit));
+                    }
+                }
+                Ok(Expr::Number(number))
+            }
+            Some(Token::Based(value)) => Ok(Expr::Number(Number::from_bigint(value))),
+            Some(Token::Ident(name)) => {
+                if let Some(money) = self.currency(&name) {
+                    return Ok(money);
+                }
+                if self.peek() != Some(&Token::LParen) {
+                    self.check_ident(self.pos - 1, &name)?;
+                    return Ok(Expr::Ident(name));
+                }
+                self.check_function(self.pos - 1, &name)?;
+                self.pos += 1;
+                Ok(Expr::Call(name, self.items(Token::RParen)?))
+            }
+            Some(Token::LBracket) => Ok(Expr::List(self.items(Token::RBracket)?)),
+            Some(Token::LParen) => {
+                let expr = self.expr()?;
+                self.close(Token::RParen)?;
+                Ok(expr)
+            }
+            _ => {
+                let error = self
+                    .error(self.pos - 1, "Invalid expression: expected number.")
+                    .expected(&["number", "("]);
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Parses comma separated expressions up to the closing bracket.
+    fn items(&mut self, close: Token) -> Result<Vec<Expr>> {
+        let mut items = Vec::new();
+        if self.peek() != Some(&close) {
+            items.push(self.expr()?);
+            while self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                items.push(self.expr()?);
+            }
+        }
+        self.close(close)?;
+        Ok(items)
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{Error, Result};
+use num_bigint::BigInt;
+use num_traits::{One, Signed};
+use regex::Regex;
+
+use super::{evaluate_number, parser::Parser};
+use crate::utils::locale::Locale;
+
+/// Base used to print integers.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Radix {
+    Bin,
+    Oct,
+    Dec,
+    Hex,
+}
+
+impl Radix {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "bin" | "binary" => Some(Radix::Bin),
+            "oct" | "octal" => Some(Radix::Oct),
+            "dec" | "decimal" => Some(Radix::Dec),
+            "hex" | "hexadecimal" => Some(Radix::Hex),
+            _ => None,
+        }
+    }
+
+    fn base(&self) -> u32 {
+        match self {
+            Radix::Bin => 2,
+            Radix::Oct => 8,
+            Radix::Dec => 10,
+            Radix::Hex => 16,
+        }
+    }
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            Radix::Bin => "0b",
+            Radix::Oct => "0o",
+            Radix::Dec => "",
+            Radix::Hex => "0x",
+        }
+    }
+}
+
+/// Fixed-width integer type like `i8` or `u128`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct IntWidth {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+impl IntWidth {
+    /// Returns width by type name like `u16`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let signed = match name.chars().next()? {
+            'i' | 'I' => true,
+            'u' | 'U' => false,
+            _ => return None,
+        };
+        let bits = name[1..].parse::<u32>().ok()?;
+        [8, 16, 32, 64, 128]
+            .contains(&bits)
+            .then_some(Self { bits, signed })
+    }
+
+    /// Wraps value to the width like a cast in two's complement does.
+    pub fn wrap(&self, value: &BigInt) -> BigInt {
+        let modulus = BigInt::one() << self.bits;
+        let unsigned = ((value % &modulus) + &modulus) % &modulus;
+        if self.signed && unsigned >= (BigInt::one() << (self.bits - 1)) {
+            unsigned - modulus
+        } else {
+            unsigned
+        }
+    }
+}
+
+/// Integer printed in a base, optionally as a fixed-width integer.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RadixResult {
+    /// Value after wrapping to the width.
+    pub value: BigInt,
+    pub radix: Radix,
+    pub width: Option<IntWidth>,
+}
+
+impl RadixResult {
+    /// Returns String like `0xff`. Negative values of fixed width are printed as their two's complement bits.
+    pub fn txt(&self) -> String {
+        let base = self.radix.base();
+        if self.radix == Radix::Dec {
+            return self.value.to_string();
+        }
+        let (negative, digits) = match self.width {
+            Some(width) => {
+                let bits = IntWidth {
+                    bits: width.bits,
+                    signed: false,
+                }
+                .wrap(&self.value);
+                // Pad to the full width: 8 bits are `0b00001111` or `0x0f`.
+                let len = match self.radix {
+                    Radix::Bin => width.bits as usize,
+                    Radix::Hex => width.bits as usize / 4,
+                    _ => 0,
+                };
+                (
+                    false,
+                    format!("{:0>len$}", bits.to_str_radix(base), len = len),
+                )
+            }
+            None => (
+                self.value.is_negative(),
+                self.value.abs().to_str_radix(base),
+            ),
+        };
+        format!(
+            "{}{}{}",
+            if negative { "-" } else { "" },
+            self.radix.prefix(),
+            digits
+        )
+    }
+}
+
+/// Evaluates programmer query like `255 to hex`, `0xff to bin`, `-1 as u16` or `-1 as i8 to hex`.
+/// `^` is the power in these queries, so `2^8 to hex` is `0x100`, xor is written as `xor`.
+pub fn radix_convert(query: &str) -> Result<RadixResult> {
+    radix_convert_with(query, &Locale::default())
+}
+
+/// Same as `radix_convert`, but numbers are read using separators of given locale.
+pub fn radix_convert_with(query: &str, locale: &Locale) -> Result<RadixResult> {
+    let target = r"\s*(?:\bto\b|\bas\b|\bin\b|:)\s*([a-zA-Z]+[0-9]*)";
+    let regex = Regex::new(&format!(r"^(.+?)((?:{})+)\s*$", target)).unwrap();
+    let Some(caps) = regex.captures(query) else {
+        return Err(Error::InvalidQuery(
+            "Invalid conversion query: error parsing parts.".to_string(),
+        ));
+    };
+    let mut radix = Radix::Dec;
+    let mut width = None;
+    for target in Regex::new(target).unwrap().captures_iter(&caps[2]) {
+        if let Some(r) = Radix::from_name(&target[1]) {
+            radix = r;
+        } else if let Some(w) = IntWidth::from_name(&target[1]) {
+            width = Some(w);
+        } else {
+            return Err(Error::InvalidQuery(
+                "Invalid conversion query: unknown base or integer type.".to_string(),
+            ));
+        }
+    }
+    let expr = Parser::parse_conversion(&caps[1], locale)?;
+    let Some(value) = evaluate_number(&expr)?.to_integer() else {
+        return Err(Error::Math(
+            "Only integers can be converted to other base.".to_string(),
+        ));
+    };
+    let value = match width {
+        Some(width) => width.wrap(&value),
+        None => value,
+    };
+    Ok(RadixResult {
+        value,
+        radix,
+        width,
+    })
+}
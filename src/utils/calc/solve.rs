@@ -0,0 +1,452 @@
+use crate::{Error, Result};
+use regex::Regex;
+
+use super::{evaluate_expr, number::Number, parser::Expr, parser::Parser, target_unit, Value};
+use crate::utils::{
+    diagnostic::shift,
+    format::{format_number, FormatOptions},
+    locale::Locale,
+    units::{Measurement, Unit},
+};
+
+/// Units tried for an unknown when the query doesn't give its unit.
+const BASE_UNITS: [Unit; 15] = [
+    Unit::Meter,
+    Unit::Kilogram,
+    Unit::Second,
+    Unit::Ampere,
+    Unit::Volt,
+    Unit::Watt,
+    Unit::Joule,
+    Unit::Byte,
+    Unit::Pixel,
+    Unit::Celsius,
+    Unit::Pascal,
+    Unit::MeterPerSecond,
+    Unit::SquareMeter,
+    Unit::CubicMeter,
+    Unit::Radian,
+];
+
+/// Iterations of bisection, enough to narrow any bracket to adjacent floats.
+const BISECTION_STEPS: usize = 200;
+
+/// Even steps in each step of the sampling grid.
+const SUBDIVISIONS: usize = 16;
+
+/// Times the samples around minima of the residual are subdivided again.
+const REFINEMENTS: usize = 4;
+
+/// Samples after which no more refinements are made, for fast oscillating functions.
+const MAX_SAMPLES: usize = 20_000;
+
+/// Roots given in a solution, the ones closest to zero are kept for periodic equations.
+const MAX_ROOTS: usize = 10;
+
+/// Roots of an equation with one unknown.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Solution {
+    pub variable: String,
+    /// Roots in ascending order.
+    pub roots: Vec<f64>,
+    /// Unit of the roots, `None` for plain numbers.
+    pub unit: Option<Unit>,
+    /// There are more roots than `roots`, like for `sin(x) = 0`.
+    pub more: bool,
+}
+
+impl Solution {
+    /// Returns String formated roots using given format options, like `x = -2 or x = 2`.
+    /// Dropped roots are marked with `or …`.
+    pub fn txt_with(&self, options: &FormatOptions) -> String {
+        let roots = self
+            .roots
+            .iter()
+            .map(|root| {
+                format!(
+                    "{} = {}",
+                    self.variable,
+                    value_txt(*root, self.unit, options)
+                )
+            })
+            .chain(self.more.then(|| "…".to_string()));
+        roots.collect::<Vec<_>>().join(" or ")
+    }
+
+    pub fn txt(&self) -> String {
+        self.txt_with(&FormatOptions::default())
+    }
+}
+
+fn value_txt(value: f64, unit: Option<Unit>, options: &FormatOptions) -> String {
+    match unit {
+        Some(unit) => Measurement::new(value, unit).txt_with(options),
+        None => format_number(value, options),
+    }
+}
+
+/// Equation `left = right` with the unknown of given unit.
+struct Equation<'a> {
+    left: &'a Expr,
+    right: &'a Expr,
+    variable: &'a str,
+    unit: Option<Unit>,
+}
+
+impl Equation<'_> {
+    /// Returns `left - right` in base units for the value of the unknown.
+    fn residual(&self, x: f64) -> Option<f64> {
+        let value = match self.unit {
+            Some(unit) => Expr::Quantity(Number::Float(x), unit),
+            None => Expr::Number(Number::Float(x)),
+        };
+        let left = evaluate_expr(&substitute(self.left, self.variable, &value)).ok()?;
+        let right = evaluate_expr(&substitute(self.right, self.variable, &value)).ok()?;
+        let residual = match (left, right) {
+            (Value::Number(a), Value::Number(b)) if !a.is_complex() && !b.is_complex() => {
+                a.to_f64() - b.to_f64()
+            }
+            (Value::Measurement(a), Value::Measurement(b)) => {
+                let (a, b) = (a.to_base(), b.to_base());
+                if a.unit != b.unit {
+                    return None;
+                }
+                a.value - b.value
+            }
+            _ => return None,
+        };
+        residual.is_finite().then_some(residual)
+    }
+
+    /// Finds roots by sign changes on a logarithmic grid refined by bisection.
+    fn roots(&self) -> Result<Vec<f64>> {
+        let mut grid: Vec<f64> = (-24..=36).map(|k| 10f64.powf(k as f64 / 4.)).collect();
+        grid = grid
+            .iter()
+            .rev()
+            .map(|x| -x)
+            .chain([0.])
+            .chain(grid.iter().copied())
+            .collect();
+        // Steps of the grid are split evenly, so close roots like 2 and 3 get own steps.
+        grid = grid
+            .windows(2)
+            .flat_map(|pair| steps(pair[0], pair[1]))
+            .chain(grid.last().copied())
+            .collect();
+        let mut samples: Vec<(f64, Option<f64>)> =
+            grid.iter().map(|x| (*x, self.residual(*x))).collect();
+        let defined: Vec<f64> = samples.iter().filter_map(|(_, f)| *f).collect();
+        if !defined.is_empty() && defined.iter().all(|f| *f == 0.) {
+            return Err(Error::Math(
+                "The equation holds for every value.".to_string(),
+            ));
+        }
+        // Adjacent samples solving the equation, like in `floor(x) = 3`, are a part of an interval.
+        let zero = |f: &Option<f64>| *f == Some(0.);
+        if let Some(i) = samples
+            .windows(2)
+            .position(|p| zero(&p[0].1) && zero(&p[1].1))
+        {
+            let before = samples[..i].iter().rposition(|(_, f)| !zero(f));
+            let after = samples[i..]
+                .iter()
+                .position(|(_, f)| !zero(f))
+                .map(|j| i + j);
+            let (first, last) = (
+                before.map_or(i, |j| j + 1),
+                after.map_or(samples.len(), |j| j) - 1,
+            );
+            let from = match before {
+                Some(j) => self.edge(samples[first].0, samples[j].0),
+                None => samples[first].0,
+            };
+            let to = match after {
+                Some(j) => self.edge(samples[last].0, samples[j].0),
+                None => samples[last].0,
+            };
+            let options = FormatOptions::default();
+            return Err(Error::Math(format!(
+                "The equation holds for every value from {} to {}.",
+                value_txt(from, self.unit, &options),
+                value_txt(to, self.unit, &options)
+            )));
+        }
+        for _ in 0..REFINEMENTS {
+            if samples.len() > MAX_SAMPLES {
+                break;
+            }
+            samples = self.refine(&samples);
+        }
+        let mut roots = Vec::new();
+        for pair in samples.windows(2) {
+            let [(a, Some(fa)), (b, Some(fb))] = pair else {
+                continue;
+            };
+            if *fa == 0. {
+                roots.push(*a);
+            } else if fa.signum() != fb.signum() && *fb != 0. {
+                if let Some(root) = self.bisect(*a, *b, *fa, *fb) {
+                    roots.push(root);
+                }
+            }
+        }
+        if let Some((x, Some(f))) = samples.last() {
+            if *f == 0. {
+                roots.push(*x);
+            }
+        }
+        // Roots where the function touches zero without crossing it, like in `(x - 2)^2 = 0`.
+        for triple in samples.windows(3) {
+            let [(a, Some(fa)), _, (b, Some(fb))] = triple else {
+                continue;
+            };
+            if minimum(triple) {
+                if let Some(root) = self.touch(*a, *b, fa.abs().max(fb.abs())) {
+                    roots.push(root);
+                }
+            }
+        }
+        roots.sort_by(f64::total_cmp);
+        roots.dedup();
+        Ok(roots)
+    }
+
+    /// Samples steps around minima of `|left - right|` which don't reach zero,
+    /// the function may cross zero twice between samples, like `(x - 1) * (x - 1.01)`.
+    fn refine(&self, samples: &[(f64, Option<f64>)]) -> Vec<(f64, Option<f64>)> {
+        // Sample `i` is a minimum if its neighbours are further from zero.
+        let minima: Vec<bool> = [false]
+            .into_iter()
+            .chain(samples.windows(3).map(minimum))
+            .chain([false])
+            .collect();
+        let mut refined = Vec::with_capacity(samples.len());
+        for (i, pair) in samples.windows(2).enumerate() {
+            refined.push(pair[0]);
+            if minima[i] || minima[i + 1] {
+                let inner = steps(pair[0].0, pair[1].0).skip(1);
+                refined.extend(inner.map(|x| (x, self.residual(x))));
+            }
+        }
+        refined.extend(samples.last().copied());
+        refined
+    }
+
+    /// Returns the last value solving the equation from `inside` towards `outside`, by bisection.
+    fn edge(&self, mut inside: f64, mut outside: f64) -> f64 {
+        for _ in 0..BISECTION_STEPS {
+            let mid = (inside + outside) / 2.;
+            if mid == inside || mid == outside {
+                break;
+            }
+            if self.residual(mid) == Some(0.) {
+                inside = mid;
+            } else {
+                outside = mid;
+            }
+        }
+        inside
+    }
+
+    /// Minimizes `|left - right|` by golden section search, the minimum is a root if it's zero.
+    fn touch(&self, mut a: f64, mut b: f64, scale: f64) -> Option<f64> {
+        let bracket = (a, b);
+        let ratio = (5f64.sqrt() - 1.) / 2.;
+        for _ in 0..BISECTION_STEPS {
+            let c = b - (b - a) * ratio;
+            let d = a + (b - a) * ratio;
+            if c == d {
+                break;
+            }
+            if self.residual(c)?.abs() < self.residual(d)?.abs() {
+                b = d;
+            } else {
+                a = c;
+            }
+        }
+        let root = (a + b) / 2.;
+        (self.residual(root)?.abs() <= 1e-12 * scale.max(1.)).then(|| self.polish(root, bracket))
+    }
+
+    fn bisect(&self, mut a: f64, mut b: f64, mut fa: f64, fb: f64) -> Option<f64> {
+        let bracket = (a, b);
+        for _ in 0..BISECTION_STEPS {
+            let mid = (a + b) / 2.;
+            if mid == a || mid == b {
+                break;
+            }
+            let fm = self.residual(mid)?;
+            if fm == 0. {
+                return Some(self.polish(mid, bracket));
+            }
+            if fm.signum() == fa.signum() {
+                (a, fa) = (mid, fm);
+            } else {
+                b = mid;
+            }
+        }
+        let root = if fa.abs() < fb.abs() { a } else { b };
+        // A sign change around a pole, like in `1/x = 0`, is not a root.
+        let scale = fa.abs().max(fb.abs()).max(1.);
+        (self.residual(root)?.abs() <= 1e-6 * scale).then(|| self.polish(root, bracket))
+    }
+
+    /// Returns the shortest rounding of the root which solves the equation as well,
+    /// so `3.9999999999999996` is `4` and a double root `2.00000002` is `2`.
+    /// The rounding stays in the bracket of the root, so it doesn't jump to a near root.
+    fn polish(&self, root: f64, (a, b): (f64, f64)) -> f64 {
+        let Some(residual) = self.residual(root) else {
+            return root;
+        };
+        (0..15)
+            .filter_map(|digits| format!("{:.*e}", digits, root).parse::<f64>().ok())
+            .filter(|rounded| (a.min(b)..=a.max(b)).contains(rounded))
+            .find(|rounded| {
+                self.residual(*rounded)
+                    .is_some_and(|r| r.abs() <= residual.abs())
+            })
+            .unwrap_or(root)
+    }
+}
+
+/// Checks if the middle of three samples is closer to zero than others, on the same side of it.
+/// The middle may be a root itself, then another root can be near it.
+fn minimum(triple: &[(f64, Option<f64>)]) -> bool {
+    let [(_, Some(fa)), (_, Some(fm)), (_, Some(fb))] = triple else {
+        return false;
+    };
+    fa.signum() == fb.signum()
+        && (*fm == 0. || fm.signum() == fa.signum())
+        && fm.abs() < fa.abs()
+        && fm.abs() <= fb.abs()
+}
+
+/// Returns evenly spaced points from `a` to `b`, without `b`.
+fn steps(a: f64, b: f64) -> impl Iterator<Item = f64> {
+    (0..SUBDIVISIONS).map(move |i| a + (b - a) * i as f64 / SUBDIVISIONS as f64)
+}
+
+/// Replaces the unknown with its value.
+fn substitute(expr: &Expr, variable: &str, value: &Expr) -> Expr {
+    let sub = |e: &Expr| Box::new(substitute(e, variable, value));
+    match expr {
+        Expr::Ident(name) if name == variable => value.clone(),
+        Expr::Neg(e) => Expr::Neg(sub(e)),
+        Expr::Not(e) => Expr::Not(sub(e)),
+        Expr::Factorial(e) => Expr::Factorial(sub(e)),
+        Expr::Binary(op, a, b) => Expr::Binary(*op, sub(a), sub(b)),
+        Expr::Call(name, args) => Expr::Call(
+            name.clone(),
+            args.iter()
+                .map(|e| substitute(e, variable, value))
+                .collect(),
+        ),
+        Expr::List(items) => Expr::List(
+            items
+                .iter()
+                .map(|e| substitute(e, variable, value))
+                .collect(),
+        ),
+        e => e.clone(),
+    }
+}
+
+/// Solves query like `solve x: 2x + 3 = 11` or `solve t: 100km = 80km/h * t to min`.
+/// Equation without `=` is compared with zero. Roots are found numerically in ±1e9.
+pub fn solve(query: &str) -> Result<Solution> {
+    solve_with(query, &Locale::default())
+}
+
+/// Same as `solve`, but numbers are read using separators of given locale.
+pub fn solve_with(query: &str, locale: &Locale) -> Result<Solution> {
+    let regex =
+        Regex::new(r"(?i)^\s*solve\s+([a-zA-Z_]\w*)\s*:\s*(.+?)(?:\s+(?:to|in|as)\s+(\S+))?\s*$")
+            .unwrap();
+    let Some(caps) = regex.captures(query) else {
+        return Err(Error::InvalidQuery(
+            "Invalid query: expected 'solve x: equation'.".to_string(),
+        ));
+    };
+    let variable = &caps[1];
+    let (left, right) = match caps[2].split('=').collect::<Vec<_>>().as_slice() {
+        [left] => (left.to_string(), "0".to_string()),
+        [left, right] => (left.to_string(), right.to_string()),
+        _ => {
+            return Err(Error::InvalidQuery(
+                "Invalid equation: expected one '='.".to_string(),
+            ))
+        }
+    };
+    let left_len = left.len();
+    let target = match caps.get(3) {
+        Some(target) => match target_unit(target.as_str(), locale) {
+            Some(unit) => Some(unit),
+            None => {
+                return Err(Error::InvalidQuery(
+                    "Invalid conversion query: error parsing to part.".to_string(),
+                ))
+            }
+        },
+        None => None,
+    };
+    // Spans of errors in the sides point into the query.
+    let offset = caps.get(2).map_or(0, |m| m.start());
+    let left =
+        Parser::parse_with_variable(&left, locale, variable).map_err(|e| shift(e, offset))?;
+    let right = Parser::parse_with_variable(&right, locale, variable)
+        .map_err(|e| shift(e, offset + left_len + 1))?;
+
+    let candidates = match target {
+        Some(unit) => vec![Some(Measurement::new(0., unit).to_base().unit)],
+        None => [None]
+            .into_iter()
+            .chain(BASE_UNITS.into_iter().map(Some))
+            .collect(),
+    };
+    for unit in candidates {
+        let equation = Equation {
+            left: &left,
+            right: &right,
+            variable,
+            unit,
+        };
+        // The unit fits if both sides can be evaluated and compared.
+        if equation.residual(1.).is_none() && equation.residual(-1.).is_none() {
+            continue;
+        }
+        let mut roots = equation.roots()?;
+        if roots.is_empty() {
+            return Err(Error::Math("No solution found in ±1e9.".to_string()));
+        }
+        // Periodic equations like `sin(x) = 0` have roots all over the range.
+        let more = roots.len() > MAX_ROOTS;
+        if more {
+            roots.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+            roots.truncate(MAX_ROOTS);
+            roots.sort_by(f64::total_cmp);
+        }
+        if let (Some(target), Some(base)) = (target, unit) {
+            for root in roots.iter_mut() {
+                *root = Measurement::new(*root, base).to_other(target).value;
+            }
+        }
+        return Ok(Solution {
+            variable: variable.to_string(),
+            roots,
+            unit: target.or(unit),
+            more,
+        });
+    }
+    // Errors other than units, like factorial of a non-integer in `x! = 120`, are reported as is.
+    let value = Expr::Number(Number::Float(1.));
+    for side in [&left, &right] {
+        match evaluate_expr(&substitute(side, variable, &value)) {
+            Err(Error::IncompatibleUnits(_)) | Ok(_) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Err(Error::IncompatibleUnits(
+        "Invalid equation: sides have different units.".to_string(),
+    ))
+}
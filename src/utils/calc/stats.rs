@@ -0,0 +1,151 @@
+use crate::{Error, Result};
+
+use super::{number::Number, Value};
+use crate::utils::units::{Measurement, Unit};
+
+/// Names of functions taking a list of values.
+pub(super) const STATISTICS: [&str; 7] = [
+    "sum",
+    "product",
+    "mean",
+    "median",
+    "variance",
+    "stdev",
+    "percentile",
+];
+
+pub(super) fn is_statistic(name: &str) -> bool {
+    STATISTICS.contains(&name)
+}
+
+fn flatten(values: Vec<Value>, items: &mut Vec<Value>) {
+    for value in values {
+        match value {
+            Value::List(list) => flatten(list, items),
+            value => items.push(value),
+        }
+    }
+}
+
+/// Computes statistic over arguments like `mean(3, 5, 9)`, `median([1, 2, 3])` or `percentile([...], 90)`.
+/// Lists of measurements give a measurement in the unit of the first item.
+pub(super) fn statistic(name: &str, mut args: Vec<Value>) -> Result<Value> {
+    let percent = match name {
+        "percentile" => match args.pop() {
+            Some(Value::Number(p)) => Some(p),
+            _ => {
+                return Err(Error::Math(
+                    "Invalid expression: 'percentile' takes a list and a percent.".to_string(),
+                ))
+            }
+        },
+        _ => None,
+    };
+    let mut items = Vec::new();
+    flatten(args, &mut items);
+    let Some(first) = items.first() else {
+        return Err(Error::Math(format!(
+            "Invalid expression: '{}' needs at least one value.",
+            name
+        )));
+    };
+    let unit = match first {
+        Value::Measurement(m) => Some(m.unit),
+        _ => None,
+    };
+    let numbers = items
+        .into_iter()
+        .map(|item| numeric(item, unit))
+        .collect::<Result<Vec<_>>>()?;
+    let result = match name {
+        "sum" => sum(numbers),
+        "product" if unit.is_none() => numbers.into_iter().fold(Number::from_int(1), |a, b| a * b),
+        "mean" => mean(numbers)?,
+        "median" => percentile(numbers, &Number::from_int(50))?,
+        "variance" if unit.is_none() => variance(numbers)?,
+        "stdev" => variance(numbers)?.sqrt(),
+        "percentile" => percentile(numbers, &percent.unwrap())?,
+        _ => {
+            return Err(Error::Math(format!(
+                "Invalid expression: '{}' is not defined for measurements.",
+                name
+            )))
+        }
+    };
+    Ok(match unit {
+        Some(unit) => Value::Measurement(Measurement::new(result.to_f64(), unit)),
+        None => Value::Number(result),
+    })
+}
+
+/// Returns number of the item, measurements are converted to the unit.
+fn numeric(item: Value, unit: Option<Unit>) -> Result<Number> {
+    match (item, unit) {
+        (Value::Number(n), None) => Ok(n),
+        (Value::Measurement(m), Some(unit)) => {
+            if m.to_base().unit != Measurement::new(0., unit).to_base().unit {
+                return Err(Error::IncompatibleUnits(
+                    "Cannot add measurements with different units.".to_string(),
+                ));
+            }
+            Ok(Number::Float(m.to_other(unit).value))
+        }
+        _ => Err(Error::IncompatibleUnits(
+            "Invalid expression: can't mix numbers and measurements in a list.".to_string(),
+        )),
+    }
+}
+
+fn sum(numbers: Vec<Number>) -> Number {
+    numbers.into_iter().fold(Number::from_int(0), |a, b| a + b)
+}
+
+fn mean(numbers: Vec<Number>) -> Result<Number> {
+    let count = Number::from_int(numbers.len() as i64);
+    sum(numbers) / count
+}
+
+/// Sample variance (divided by `n - 1`).
+fn variance(numbers: Vec<Number>) -> Result<Number> {
+    if numbers.len() < 2 {
+        return Err(Error::Math(
+            "Invalid expression: variance needs at least two values.".to_string(),
+        ));
+    }
+    let count = Number::from_int(numbers.len() as i64 - 1);
+    let mean = mean(numbers.clone())?;
+    let squares = numbers.into_iter().map(|x| {
+        let d = x - mean.clone();
+        d.clone() * d
+    });
+    sum(squares.collect()) / count
+}
+
+/// Percentile with linear interpolation between closest ranks, `50` is the median.
+fn percentile(mut numbers: Vec<Number>, percent: &Number) -> Result<Number> {
+    if numbers.iter().any(Number::is_complex) {
+        return Err(Error::Math("Complex numbers can't be ordered.".to_string()));
+    }
+    if numbers.iter().any(|n| n.to_f64().is_nan()) {
+        return Err(Error::Math("NaN can't be ordered.".to_string()));
+    }
+    // Written with `>=` and `<=`, so NaN is rejected too.
+    let in_range = percent >= &Number::from_int(0) && percent <= &Number::from_int(100);
+    if percent.is_complex() || !in_range {
+        return Err(Error::Math(
+            "Percentile must be between 0 and 100.".to_string(),
+        ));
+    }
+    numbers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let rank =
+        (percent.clone() / Number::from_int(100))? * Number::from_int(numbers.len() as i64 - 1);
+    let lower = rank.floor();
+    let fraction = rank - lower.clone();
+    let lower = lower.to_f64() as usize;
+    let low = numbers[lower].clone();
+    if fraction.is_zero() {
+        return Ok(low);
+    }
+    let high = numbers[(lower + 1).min(numbers.len() - 1)].clone();
+    Ok(low.clone() + fraction * (high - low))
+}
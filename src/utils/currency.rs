@@ -1,84 +1,621 @@
-use anyhow::{bail, Result};
+use std::{
+    str::FromStr,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
 use regex::Regex;
-use serde_json::Value;
+pub use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
+
+use super::{
+    assets,
+    calc::{money_eval_with, number::Number, Money},
+    diagnostic::{part_error, part_offsets, ParseError},
+    format::{format_rational, FormatOptions, Rounding},
+    history::{Date, EcbHistory, HistoricalRates, RatesAt},
+    iso4217,
+    locale::Locale,
+    rates::{CoinGecko, OpenErApi, RateCache, RateProvider, RateTable},
+    translate::Translator,
+};
+use crate::{Error, Result};
+
+/// Currency of the rate table of converters.
+const DEFAULT_BASE: &str = "USD";
+
 #[derive(Debug)]
 pub struct Currency {
     pub currency: String,
-    pub amount: f64,
+    /// Fixed-point amount, so sums of money don't pick up binary float errors.
+    pub amount: Decimal,
+    /// Age of the rates used for conversion, old when they are taken from the cache offline.
+    pub age: Option<Duration>,
 }
 
 impl Currency {
-    pub fn new(currency: String, amount: f64) -> Self {
-        Self { currency, amount }
+    /// Amount is taken by its shortest decimal form, so `0.1` is exactly `0.1`.
+    /// Values out of the decimal range, like `1e30` or `NaN`, are rejected.
+    pub fn new(currency: String, amount: f64) -> Result<Self> {
+        Ok(Self::from_decimal(currency, money_amount(amount)?))
+    }
+
+    pub fn from_decimal(currency: String, amount: Decimal) -> Self {
+        Self {
+            currency,
+            amount,
+            age: None,
+        }
+    }
+
+    /// Count of digits after the decimal point of the currency, `2` for unknown ones.
+    pub fn precision(&self) -> u32 {
+        iso4217::find(&self.currency).map_or(2, |info| info.minor_units as u32)
+    }
+
+    /// Returns the amount rounded to the minor units of the currency, like cents.
+    pub fn rounded(&self, rounding: Rounding) -> Currency {
+        let amount = self
+            .amount
+            .round_dp_with_strategy(self.precision(), strategy(rounding));
+        Currency {
+            currency: self.currency.clone(),
+            amount,
+            age: self.age,
+        }
     }
 
     pub fn txt(&self) -> String {
-        format!("{} {}", self.amount, self.currency)
-    }
-
-    pub fn from_str(query: &String) -> Result<Currency> {
-        let mut curr = Currency::new(String::new(), 0.);
-        let num_str = query
-            .chars()
-            .take_while(|c| c.is_digit(10) || c == &'.')
-            .collect::<String>();
-        match num_str.parse::<f64>() {
-            Ok(v) => curr.amount = v,
-            Err(_) => {}
+        self.txt_with(&FormatOptions::default())
+    }
+
+    /// Returns String formated amount and currency using given format options.
+    /// Without fixed precision in the options, known currencies are rounded to their minor units
+    /// using the rounding of the options. With `options.symbols` the sign is placed by the locale,
+    /// like `$1,234.56` or `1 234,56 ₴`.
+    pub fn txt_with(&self, options: &FormatOptions) -> String {
+        let info = iso4217::find(&self.currency);
+        let rounded;
+        let options = match info {
+            Some(info) if options.decimals.is_none() && options.significant.is_none() => {
+                rounded = FormatOptions {
+                    decimals: Some(info.minor_units as usize),
+                    ..options.clone()
+                };
+                &rounded
+            }
+            _ => options,
         };
-        let curr_part = query.chars().skip(num_str.len()).collect::<String>();
-        if curr_part.is_empty() {
-            bail!("Invalid conversion query: error parsing unit part.");
+        let number = format_rational(&to_rational(self.amount), options);
+        match info.and_then(|info| info.symbol) {
+            Some(symbol) if options.symbols => {
+                if options.locale.decimal != '.' {
+                    format!("{} {}", number, symbol)
+                } else if let Some(number) = number.strip_prefix('-') {
+                    format!("-{}{}", symbol, number)
+                } else {
+                    format!("{}{}", symbol, number)
+                }
+            }
+            _ => options.with_name(number, &self.currency.to_uppercase(), &self.currency),
         }
-        curr.currency = curr_part;
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(query: &str) -> Result<Currency> {
+        Currency::parse(query, &Locale::default())
+    }
 
-        Ok(curr)
+    /// Parses amount and currency like `1 234,5 uah`, `$5` or `5 bucks` using separators of given locale.
+    /// The currency is stored by its code.
+    pub fn parse(query: &str, locale: &Locale) -> Result<Currency> {
+        // The number may follow the sign, like in `$5`, other text before it is rejected.
+        let number = query
+            .char_indices()
+            .find_map(|(i, _)| Some((i, locale.normalize_prefix(&query[i..])?)))
+            .filter(|(_, (_, len))| *len > 0);
+        let (amount, name_span) = match number {
+            Some((start, (number, len))) => {
+                let Some(amount) = parse_decimal(&number) else {
+                    return Err(Error::Math(format!(
+                        "The amount {} is out of range.",
+                        &query[start..start + len]
+                    )));
+                };
+                let prefix = &query[..start];
+                if !prefix.trim().is_empty() {
+                    resolve(prefix, prefix.len() - prefix.trim_start().len())?;
+                }
+                match query[start + len..].trim().is_empty() {
+                    true if start > 0 => (amount, 0..start),
+                    _ => (amount, start + len..query.len()),
+                }
+            }
+            None => (Decimal::ZERO, 0..query.len()),
+        };
+        let name = &query[name_span.clone()];
+        let start = name_span.start + name.len() - name.trim_start().len();
+        Ok(Currency::from_decimal(
+            resolve(name, start)?.to_string(),
+            amount,
+        ))
     }
 }
 
-// Converts currency from args.
-pub async fn curr_convert(from: &str, to: &str, amount: f64) -> Result<Currency> {
-    let response = reqwest::get(
-        "https://open.er-api.com/v6/latest/".to_owned() + from.to_uppercase().as_str(),
+/// Reads normalized number like `-1234.5` or `1.5e3`.
+fn parse_decimal(number: &str) -> Option<Decimal> {
+    Decimal::from_str(number)
+        .or_else(|_| Decimal::from_scientific(number))
+        .ok()
+}
+
+/// Decimal with the shortest digits that round-trip to the same `f64`.
+fn to_decimal(value: f64) -> Option<Decimal> {
+    parse_decimal(&format!("{:e}", value)).map(|d| d.normalize())
+}
+
+/// Decimal of an amount of money, an error for values out of the decimal range.
+fn money_amount(amount: f64) -> Result<Decimal> {
+    to_decimal(amount)
+        .ok_or_else(|| Error::Math(format!("{} can't be used as an amount of money.", amount)))
+}
+
+/// Decimal of a real number, exact rationals are divided with the decimal precision.
+fn number_to_decimal(n: &Number) -> Option<Decimal> {
+    match n {
+        Number::Exact(value) => {
+            let numer = Decimal::from_str(&value.numer().to_string()).ok()?;
+            let denom = Decimal::from_str(&value.denom().to_string()).ok()?;
+            numer.checked_div(denom)
+        }
+        Number::Float(value) => to_decimal(*value),
+        Number::Complex(_) => None,
+    }
+}
+
+fn to_rational(value: Decimal) -> BigRational {
+    BigRational::new(
+        BigInt::from(value.mantissa()),
+        BigInt::from(10).pow(value.scale()),
     )
-    .await?
-    .json::<Value>()
-    .await?;
-    let rates = response["rates"].as_object();
-    if let None = rates {
-        bail!("Invalid conversion query: error parsing rates part.");
+}
+
+fn strategy(rounding: Rounding) -> RoundingStrategy {
+    match rounding {
+        Rounding::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+        Rounding::HalfDown => RoundingStrategy::MidpointTowardZero,
+        Rounding::HalfEven => RoundingStrategy::MidpointNearestEven,
+        Rounding::Up => RoundingStrategy::AwayFromZero,
+        Rounding::Down => RoundingStrategy::ToZero,
+        Rounding::Ceiling => RoundingStrategy::ToPositiveInfinity,
+        Rounding::Floor => RoundingStrategy::ToNegativeInfinity,
     }
-    let rates = rates.unwrap();
-    let rate = rates.get(&to.to_uppercase());
-    if let None = rate {
-        bail!("Invalid conversion query: error parsing rate part.");
+}
+
+/// Returns code of the currency written by its code, symbol or alias.
+/// `start` is the offset of the name in the query, for the error.
+fn resolve(name: &str, start: usize) -> Result<&'static str> {
+    let name = name.trim();
+    match iso4217::lookup(name) {
+        Some(info) => Ok(info.code),
+        None => {
+            let error = ParseError::new(
+                "Invalid conversion query: error parsing unit part.",
+                start..start + name.len(),
+            )
+            .expected(&["currency code"])
+            .suggest(name, iso4217::names().chain(assets::names()));
+            Err(Error::UnknownCurrency(error))
+        }
+    }
+}
+
+/// Currency or its sub-unit, like `BTC` or `sat`.
+struct Target {
+    code: &'static str,
+    /// Name printed in results, the code or the sub-unit name.
+    name: &'static str,
+    /// Amount of the currency in one sub-unit.
+    factor: Decimal,
+}
+
+/// Same as `resolve`, but sub-units like `sats` or `gwei` are accepted too.
+fn resolve_target(name: &str, start: usize) -> Result<Target> {
+    if let Some(sub_unit) = assets::denomination(name) {
+        return Ok(Target {
+            code: sub_unit.code,
+            name: sub_unit.name,
+            factor: parse_decimal(sub_unit.factor).unwrap_or(Decimal::ONE),
+        });
     }
-    if let None = rate.unwrap().as_f64() {
-        bail!("Invalid conversion query: error parsing rate part.");
+    let code = resolve(name, start)?;
+    Ok(Target {
+        code,
+        name: code,
+        factor: Decimal::ONE,
+    })
+}
+
+/// Returns is the text a known currency code, like `usd` or `EUR`.
+pub fn is_code(text: &str) -> bool {
+    iso4217::find(text).is_some()
+}
+
+/// Context of conversions, holds the source of exchange rates and the translator.
+/// The default one uses open.er-api.com with rates cached in memory, shared by all
+/// default converters, and Google Translate.
+///
+/// Rates of any pair are computed from one table of the base currency (USD by default),
+/// so one request serves all conversions and their results are consistent.
+#[derive(Clone)]
+pub struct Converter {
+    provider: Arc<dyn RateProvider>,
+    /// Source of rates of crypto assets and metals missing in the rates of `provider`.
+    assets: Arc<dyn RateProvider>,
+    /// Source of rates for queries with a date, like `100 usd to eur on 2024-03-01`.
+    history: Arc<dyn HistoricalRates>,
+    translator: Translator,
+    base: String,
+    /// Currencies of `*favourites` in multi-target queries.
+    favourites: Vec<String>,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        static SHARED: OnceLock<Converter> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Converter::new(RateCache::new(OpenErApi::new())))
+            .clone()
     }
-    let curr = Currency::new(to.to_string(), amount * rate.unwrap().as_f64().unwrap());
-    Ok(curr)
+}
+
+impl Converter {
+    pub fn new(provider: impl RateProvider + 'static) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            assets: Arc::new(RateCache::new(CoinGecko::new())),
+            history: Arc::new(EcbHistory::new()),
+            translator: Translator::new(),
+            base: DEFAULT_BASE.to_string(),
+            favourites: Vec::new(),
+        }
+    }
+
+    /// Sets currency of the rate table used for all pairs.
+    pub fn with_base(mut self, base: &str) -> Self {
+        self.base = base.to_uppercase();
+        self
+    }
+
+    /// Sets currencies used for `*favourites` in queries like `100 usd to *favourites`.
+    pub fn with_favourites(mut self, currencies: &[&str]) -> Self {
+        self.favourites = currencies.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Uses given source of past rates, ECB reference rates are used by default.
+    pub fn with_history(mut self, history: impl HistoricalRates + 'static) -> Self {
+        self.history = Arc::new(history);
+        self
+    }
+
+    /// Uses given source of crypto and metal rates, CoinGecko is used by default.
+    /// It's asked only for currencies of `assets::ASSETS` missing in the main rates.
+    pub fn with_assets(mut self, assets: impl RateProvider + 'static) -> Self {
+        self.assets = Arc::new(assets);
+        self
+    }
+
+    /// Returns converter using rates of given date instead of the latest ones.
+    /// Assets are converted with the same past rates, if the history has them.
+    pub fn at(&self, date: Date) -> Converter {
+        let rates: Arc<dyn RateProvider> = Arc::new(RatesAt {
+            history: self.history.clone(),
+            date,
+        });
+        Converter {
+            provider: rates.clone(),
+            assets: rates,
+            ..self.clone()
+        }
+    }
+
+    /// Uses given translator for text queries, like one with other base URL.
+    pub fn with_translator(mut self, translator: Translator) -> Self {
+        self.translator = translator;
+        self
+    }
+
+    pub fn translator(&self) -> &Translator {
+        &self.translator
+    }
+
+    /// Returns the rate table all pairs are computed from.
+    pub async fn rates(&self) -> Result<RateTable> {
+        self.provider.rates(&self.base).await
+    }
+
+    /// Converts amount of one currency to other, currencies are given by codes, symbols, aliases
+    /// or sub-units like `sat`. Unknown currencies are rejected without asking the provider.
+    pub async fn convert(&self, from: &str, to: &str, amount: f64) -> Result<Currency> {
+        self.convert_decimal(from, to, money_amount(amount)?).await
+    }
+
+    /// Converts amount with rates of given date, like a transaction date of an expense report.
+    pub async fn convert_at(
+        &self,
+        from: &str,
+        to: &str,
+        amount: f64,
+        date: Date,
+    ) -> Result<Currency> {
+        self.at(date).convert(from, to, amount).await
+    }
+
+    /// Converts decimal amount, the rate is applied with decimal arithmetic and the result isn't rounded.
+    pub async fn convert_decimal(&self, from: &str, to: &str, amount: Decimal) -> Result<Currency> {
+        let from = resolve_target(from, 0)?;
+        resolve_target(to, 0)?;
+        let amount = amount
+            .checked_mul(from.factor)
+            .and_then(|amount| Number::parse(&amount.to_string()))
+            .ok_or_else(|| Error::Math("the amount is too big.".to_string()))?;
+        self.convert_money(&Money::new(from.code, amount), to).await
+    }
+
+    /// Converts query like `5 usd to eur`, `$5 to €` or `100 hryvnia in usd`, numbers are read using separators of given locale.
+    /// Other queries are evaluated as expressions with money, see `evaluate`.
+    /// Rates of a past day are used for queries ending with a date, like `100 usd to eur on 2024-03-01`.
+    pub async fn convert_query(&self, query: &str, locale: &Locale) -> Result<Currency> {
+        let (converter, query) = self.dated(query)?;
+        converter.convert_single(query, locale).await
+    }
+
+    /// Returns converter for the date at the end of the query and the query without it.
+    fn dated<'a>(&self, query: &'a str) -> Result<(Converter, &'a str)> {
+        let regex = Regex::new(r"(?i)\s+(?:on|at)\s+(\S+)\s*$").unwrap();
+        let Some(caps) = regex.captures(query) else {
+            return Ok((self.clone(), query));
+        };
+        let date = caps.get(1).unwrap();
+        match date.as_str().parse() {
+            Ok(date) => Ok((self.at(date), &query[..caps.get(0).unwrap().start()])),
+            Err(_) => {
+                let message = format!("Invalid date '{}'.", date.as_str());
+                let error = ParseError::new(&message, date.range()).expected(&["YYYY-MM-DD"]);
+                Err(Error::Parse(error))
+            }
+        }
+    }
+
+    async fn convert_single(&self, query: &str, locale: &Locale) -> Result<Currency> {
+        match self.convert_pair(query, locale).await {
+            Err(error @ (Error::InvalidQuery(_) | Error::UnknownCurrency(_))) => {
+                match self.evaluate(query, locale).await {
+                    // The query is neither a pair nor an expression, the pair error is clearer.
+                    Err(Error::Parse(_) | Error::UnknownCurrency(_) | Error::InvalidQuery(_)) => {
+                        Err(error)
+                    }
+                    result => result,
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Evaluates expression with money like `5 usd + 3 eur to gbp`, `(1200 usd / 12) to uah`
+    /// or `20% of 300 eur in usd`. Without the target currency the result is in the first
+    /// currency of the expression. Rates are fetched once, and only if currencies differ.
+    pub async fn evaluate(&self, query: &str, locale: &Locale) -> Result<Currency> {
+        let (expression, target) = match split_target(query) {
+            Some((expression, target)) => {
+                let target = resolve_target(target.as_str(), target.start())?;
+                (expression, Some(target.name))
+            }
+            None => (query, None),
+        };
+        let money = money_eval_with(expression, locale)?;
+        let target = match target.or(money.currency()) {
+            Some(target) => target,
+            None => money.amounts()[0].0,
+        };
+        self.convert_money(&money, target).await
+    }
+
+    /// Converts sum of amounts in different currencies to one currency.
+    pub async fn convert_money(&self, money: &Money, to: &str) -> Result<Currency> {
+        let mut results = self.convert_money_many(money, &[to]).await?;
+        Ok(results.remove(0))
+    }
+
+    /// Converts sum of amounts to several currencies using one rate table.
+    pub async fn convert_money_many(
+        &self,
+        money: &Money,
+        targets: &[&str],
+    ) -> Result<Vec<Currency>> {
+        let targets = targets
+            .iter()
+            .map(|to| resolve_target(to, 0))
+            .collect::<Result<Vec<_>>>()?;
+        // Sub-units of one asset, like sats to BTC, are converted offline.
+        let table = match targets.iter().all(|to| money.currency() == Some(to.code)) {
+            true => None,
+            false => Some(self.table_for(money, &targets).await?),
+        };
+        targets
+            .iter()
+            .map(|to| exchange(money, to, table.as_ref()))
+            .collect()
+    }
+
+    /// Returns the rate table, with rates of the asset provider if the main one lacks some of the assets.
+    async fn table_for(&self, money: &Money, targets: &[Target]) -> Result<RateTable> {
+        let mut table = self.rates().await?;
+        let codes = money.amounts().iter().map(|(code, _)| *code);
+        let mut codes = codes.chain(targets.iter().map(|to| to.code));
+        if codes.any(|code| assets::ASSETS.contains(&code) && table.rate(code).is_none()) {
+            table.merge(&self.assets.rates(&self.base).await?);
+        }
+        Ok(table)
+    }
+
+    /// Converts amount of one currency to several others, fetching rates once.
+    pub async fn convert_many(
+        &self,
+        from: &str,
+        targets: &[&str],
+        amount: f64,
+    ) -> Result<Vec<Currency>> {
+        let from = resolve_target(from, 0)?;
+        let amount = to_decimal(amount)
+            .and_then(|amount| amount.checked_mul(from.factor))
+            .and_then(|amount| Number::parse(&amount.to_string()))
+            .ok_or_else(|| {
+                Error::Math(format!("{} can't be used as an amount of money.", amount))
+            })?;
+        let money = Money::new(from.code, amount);
+        self.convert_money_many(&money, targets).await
+    }
+
+    /// Converts query with several targets like `100 usd to eur, gbp, jpy` or `100 usd to *favourites`,
+    /// other queries give one result of `convert_query`.
+    pub async fn convert_query_many(&self, query: &str, locale: &Locale) -> Result<Vec<Currency>> {
+        let (converter, query) = self.dated(query)?;
+        converter.convert_query_many_now(query, locale).await
+    }
+
+    async fn convert_query_many_now(&self, query: &str, locale: &Locale) -> Result<Vec<Currency>> {
+        let Some((expression, targets)) =
+            split_target(query).filter(|(_, targets)| targets.as_str().contains([',', '*']))
+        else {
+            return Ok(vec![self.convert_single(query, locale).await?]);
+        };
+        let mut names = Vec::new();
+        let mut start = targets.start();
+        for name in targets.as_str().split(',') {
+            match name.trim() {
+                "" => {}
+                "*favourites" | "*favorites" if self.favourites.is_empty() => {
+                    return Err(Error::InvalidQuery(
+                        "Invalid conversion query: no favourite currencies are set.".to_string(),
+                    ))
+                }
+                "*favourites" | "*favorites" => names.extend(
+                    self.favourites
+                        .iter()
+                        .map(|c| Ok(resolve_target(c, 0)?.name))
+                        .collect::<Result<Vec<_>>>()?,
+                ),
+                _ => {
+                    let offset = start + name.len() - name.trim_start().len();
+                    names.push(resolve_target(name, offset)?.name)
+                }
+            }
+            start += name.len() + 1;
+        }
+        let money = money_eval_with(expression, locale)?;
+        self.convert_money_many(&money, &names).await
+    }
+
+    async fn convert_pair(&self, query: &str, locale: &Locale) -> Result<Currency> {
+        let regex = Regex::new(r"(:|to|\bin\b)").unwrap();
+        let parts: Vec<&str> = regex.split(query).collect(); //
+        let offsets = part_offsets(&regex, query);
+
+        if parts.len() != 2 {
+            return Err(Error::InvalidQuery(
+                "Invalid conversion query: error parsing parts.".to_string(),
+            ));
+        }
+        let from_part = parts[0].split_whitespace().collect::<String>();
+        let to_part = parts[1].split_whitespace().collect::<String>();
+        let from = match Currency::parse(&from_part, locale) {
+            Ok(m) => m,
+            Err(e @ Error::Math(_)) => return Err(e),
+            Err(e) => {
+                let message = "Invalid conversion query: error parsing from part.";
+                return Err(part_error(e, message, parts[0], 0));
+            }
+        };
+        let to = match Currency::parse(&to_part, locale) {
+            Ok(m) => m,
+            Err(e) => {
+                let message = "Invalid conversion query: error parsing to part";
+                return Err(part_error(e, message, parts[1], offsets[1]));
+            }
+        };
+        self.convert_decimal(&from.currency, &to.currency, from.amount)
+            .await
+    }
+}
+
+/// Splits query like `5 usd + 3 eur to gbp` at the last `to`, `in` or `:`,
+/// returns the expression and the target part.
+fn split_target(query: &str) -> Option<(&str, regex::Match<'_>)> {
+    let regex = Regex::new(r"^(.+)(?:\bto\b|\bin\b|:)(.+)$").unwrap();
+    let caps = regex.captures(query)?;
+    Some((&query[..caps.get(1)?.end()], caps.get(2)?))
+}
+
+/// Converts sum of amounts to one currency or sub-unit, the table is needed only for other currencies.
+fn exchange(money: &Money, target: &Target, table: Option<&RateTable>) -> Result<Currency> {
+    let to = target.code;
+    let too_big = || Error::Math("the converted amount is too big.".to_string());
+    let mut total = Decimal::ZERO;
+    for (from, amount) in money.amounts() {
+        let rate = match table {
+            _ if *from == to => Some(1.),
+            Some(table) => table.cross_rate(from, to),
+            None => None,
+        };
+        let Some(rate) = rate else {
+            let missing = match table {
+                Some(table) if table.rate(from).is_some() => to,
+                _ => from,
+            };
+            let message = format!("the response has no rate for {}.", missing.to_uppercase());
+            return Err(Error::Provider(message));
+        };
+        total = number_to_decimal(amount)
+            .zip(to_decimal(rate))
+            .and_then(|(amount, rate)| total.checked_add(amount.checked_mul(rate)?))
+            .ok_or_else(too_big)?;
+    }
+    let total = total.checked_div(target.factor).ok_or_else(too_big)?;
+    Ok(Currency {
+        age: table.map(RateTable::age),
+        ..Currency::from_decimal(target.name.to_string(), total)
+    })
+}
+
+// Converts currency from args.
+pub async fn curr_convert(from: &str, to: &str, amount: f64) -> Result<Currency> {
+    Converter::default().convert(from, to, amount).await
+}
+
+// Converts currency with rates of given date.
+pub async fn curr_convert_at(from: &str, to: &str, amount: f64, date: Date) -> Result<Currency> {
+    Converter::default()
+        .convert_at(from, to, amount, date)
+        .await
 }
 
 // Converts currency from String query.
-pub async fn curr_convert_q(query: &String) -> Result<Currency> {
-    let regex = Regex::new(r"(:|to)").unwrap();
-    let parts: Vec<&str> = regex.split(query).collect(); //
-
-    if parts.len() != 2 {
-        bail!("Invalid conversion query: error parsing parts.");
-    }
-    let from_part = parts[0].split_whitespace().collect::<String>();
-    let to_part = parts[1].split_whitespace().collect::<String>();
-    let from = match Currency::from_str(&from_part) {
-        Ok(m) => m,
-        Err(_) => bail!("Invalid conversion query: error parsing from part."),
-    };
-    let to = match Currency::from_str(&to_part) {
-        Ok(m) => m,
-        Err(_) => bail!("Invalid conversion query: error parsing to part"),
-    };
-    let curr = curr_convert(&from.currency, &to.currency, from.amount).await?;
-
-    Ok(curr)
+pub async fn curr_convert_q(query: &str) -> Result<Currency> {
+    curr_convert_q_with(query, &Locale::default()).await
+}
+
+// Converts currency from String query, numbers are read using separators of given locale.
+pub async fn curr_convert_q_with(query: &str, locale: &Locale) -> Result<Currency> {
+    Converter::default().convert_query(query, locale).await
+}
+
+// Converts currency from String query with several targets like `100 usd to eur, gbp, jpy`.
+pub async fn curr_convert_q_many(query: &str) -> Result<Vec<Currency>> {
+    Converter::default()
+        .convert_query_many(query, &Locale::default())
+        .await
 }
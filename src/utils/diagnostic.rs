@@ -0,0 +1,188 @@
+use std::{fmt, ops::Range};
+
+use crate::Error;
+
+/// Biggest edit distance of a suggestion.
+const MAX_DISTANCE: usize = 2;
+
+/// Count of suggestions kept.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Error of parsing a query with the place of the offending part, so UIs can highlight it.
+/// Parsers return it inside `convers::Error`, get it with `error.parse_error()`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub message: String,
+    /// Byte range of the offending part in the query, empty at the end for missing parts.
+    pub span: Range<usize>,
+    /// What was expected instead, like `unit` or `)`.
+    pub expected: Vec<String>,
+    /// Known words close to the offending part, like `km/h` for `kmh`.
+    pub suggestions: Vec<String>,
+}
+
+impl ParseError {
+    pub fn new(message: &str, span: Range<usize>) -> Self {
+        Self {
+            message: message.to_string(),
+            span,
+            expected: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    pub fn expected(mut self, expected: &[&str]) -> Self {
+        self.expected = expected.iter().map(|e| e.to_string()).collect();
+        self
+    }
+
+    /// Adds suggestions for the word from the candidates.
+    pub fn suggest<'a>(
+        mut self,
+        word: &str,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        self.suggestions = suggestions(word, candidates);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if !self.suggestions.is_empty() {
+            let quoted: Vec<String> = self
+                .suggestions
+                .iter()
+                .map(|s| format!("'{}'", s))
+                .collect();
+            write!(f, " Did you mean {}?", quoted.join(" or "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Moves span of a `ParseError` inside the error, for errors of a part parsed separately
+/// from the query. Other errors are returned as is.
+pub(crate) fn shift(mut error: Error, offset: usize) -> Error {
+    if let Some(parse_error) = error.parse_error_mut() {
+        parse_error.span = parse_error.span.start + offset..parse_error.span.end + offset;
+    }
+    error
+}
+
+/// Replaces message of error in a part of the query, like `to` part of a conversion.
+/// Span of a `ParseError` from the part with removed whitespace is moved to its place in the query.
+pub(crate) fn part_error(error: Error, message: &str, part: &str, offset: usize) -> Error {
+    let mut error = match error.parse_error() {
+        Some(_) => error,
+        None => {
+            let trimmed = part.trim_start();
+            let start = part.len() - trimmed.len();
+            ParseError::new(message, start..start + trimmed.trim_end().len()).into()
+        }
+    };
+    if let Some(parse_error) = error.parse_error_mut() {
+        parse_error.message = message.to_string();
+        parse_error.span = restore_span(part, parse_error.span.clone());
+    }
+    shift(error, offset)
+}
+
+/// Returns start offsets of parts of the text split by the regex.
+pub(crate) fn part_offsets(regex: &regex::Regex, text: &str) -> Vec<usize> {
+    [0].into_iter()
+        .chain(regex.find_iter(text).map(|m| m.end()))
+        .collect()
+}
+
+/// Maps span in text with removed whitespace back to the original text.
+pub(crate) fn restore_span(original: &str, span: Range<usize>) -> Range<usize> {
+    let mut collapsed = 0;
+    let mut start = original.len();
+    for (i, c) in original.char_indices() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if collapsed == span.start {
+            start = i;
+        }
+        if collapsed == span.end {
+            return start..i;
+        }
+        collapsed += c.len_utf8();
+    }
+    start.min(original.trim_end().len())..original.trim_end().len()
+}
+
+/// Returns candidates close to the word by edit distance (case-insensitive), closest first.
+pub fn suggestions<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let word = word.to_lowercase();
+    let mut scored: Vec<(usize, usize, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_lowercase();
+            let distance = edit_distance(&word, &lower);
+            (distance <= MAX_DISTANCE.min(word.chars().count() / 2 + 1)).then(|| {
+                (
+                    distance,
+                    usize::MAX - common_subsequence(&word, &lower),
+                    candidate,
+                )
+            })
+        })
+        .collect();
+    scored.sort();
+    let mut result: Vec<String> = Vec::new();
+    for (_, _, candidate) in scored {
+        if !result.iter().any(|r| r.eq_ignore_ascii_case(candidate)) {
+            result.push(candidate.to_string());
+        }
+    }
+    result.truncate(MAX_SUGGESTIONS);
+    result
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions and transpositions.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Length of the longest common subsequence, breaks ties so `kmh` is closer to `km/h` than to `km`.
+fn common_subsequence(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row = vec![0; b.len() + 1];
+    for c in a.chars() {
+        let mut previous = 0;
+        for j in 1..=b.len() {
+            let current = row[j];
+            row[j] = if c == b[j - 1] {
+                previous + 1
+            } else {
+                row[j].max(row[j - 1])
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
@@ -0,0 +1,243 @@
+use crate::{Error, Result};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Pow, Zero};
+use regex::Regex;
+
+use super::{
+    diagnostic::{part_error, part_offsets, ParseError},
+    format::{format_rational, FormatOptions},
+    locale::Locale,
+    units::{unit_error, Measurement, Unit},
+};
+
+/// Biggest power of ten in exact numbers, like in `1e10000`.
+const MAX_EXPONENT: u32 = 10_000;
+
+/// Measurement with exact rational value.
+/// Units are converted with exact definitional factors (1 lb = 0.45359237 kg, 1 acre = 4046.8564224 m²),
+/// so conversions round-trip precisely and only the printed value is rounded.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExactMeasurement {
+    pub value: BigRational,
+    pub unit: Unit,
+}
+
+fn ratio(numer: i64, denom: i64) -> BigRational {
+    BigRational::new(BigInt::from(numer), BigInt::from(denom))
+}
+
+fn int(value: i64) -> BigRational {
+    BigRational::from_integer(BigInt::from(value))
+}
+
+/// Returns exact factor and offset of the unit: `base = value * factor + offset`.
+/// Angles have none, degrees need π, which is irrational.
+fn definition(unit: Unit) -> Option<(BigRational, BigRational)> {
+    let factor = match unit {
+        Unit::Meter => int(1),
+        Unit::Centimeter => ratio(1, 100),
+        Unit::Millimeter => ratio(1, 1000),
+        Unit::Kilometer => int(1000),
+        Unit::Decimeter => ratio(1, 10),
+        Unit::Hectometer => int(100),
+        Unit::Decameter => int(10),
+        Unit::Kilogram => int(1),
+        Unit::Gram => ratio(1, 1000),
+        Unit::Milligram => ratio(1, 1_000_000),
+        Unit::Microgram => ratio(1, 1_000_000_000),
+        Unit::Ton => int(1000),
+        Unit::Pound => ratio(45_359_237, 100_000_000),
+        Unit::Ounce => ratio(28_349_523_125, 1_000_000_000_000),
+        Unit::Ampere => int(1),
+        Unit::Milliampere => ratio(1, 1000),
+        Unit::Volt => int(1),
+        Unit::Millivolt => ratio(1, 1000),
+        Unit::Watt => int(1),
+        Unit::Kilowatt => int(1000),
+        Unit::Joule => int(1),
+        Unit::Kilojoule => int(1000),
+        Unit::Second => int(1),
+        Unit::Minute => int(60),
+        Unit::Hour => int(3600),
+        Unit::Byte => int(1),
+        Unit::Kilobyte => int(1 << 10),
+        Unit::Megabyte => int(1 << 20),
+        Unit::Gigabyte => int(1 << 30),
+        Unit::Terabyte => int(1 << 40),
+        Unit::Petabyte => int(1 << 50),
+        Unit::Pixel => int(1),
+        Unit::Rem => int(16),
+        Unit::Em => int(16),
+        Unit::Celsius => int(1),
+        Unit::Fahrenheit => ratio(5, 9),
+        Unit::Kelvin => int(1),
+        Unit::Pascal => int(1),
+        Unit::Bar => int(100_000),
+        Unit::Atmosphere => int(101_325),
+        Unit::MeterPerSecond => int(1),
+        Unit::KilometerPerHour => ratio(5, 18),
+        Unit::MilePerHour => ratio(44_704, 100_000),
+        Unit::Liter => ratio(1, 1000),
+        Unit::Milliliter => ratio(1, 1_000_000),
+        Unit::CubicMeter => int(1),
+        Unit::SquareMeter => int(1),
+        Unit::SquareKilometer => int(1_000_000),
+        Unit::Hectare => int(10_000),
+        Unit::Acre => ratio(40_468_564_224, 10_000_000),
+        Unit::Radian | Unit::Degree => return None,
+        Unit::Mile => ratio(1_609_344, 1000),
+        Unit::Yard => ratio(9_144, 10_000),
+        Unit::Foot => ratio(3_048, 10_000),
+    };
+    let offset = match unit {
+        Unit::Fahrenheit => ratio(-160, 9),
+        Unit::Kelvin => ratio(-27_315, 100),
+        _ => BigRational::zero(),
+    };
+    Some((factor, offset))
+}
+
+/// Parses normalized decimal number like `-1234.5e-3` to exact rational.
+/// Returns `None` for powers of ten above `MAX_EXPONENT`, they would take long to build.
+pub(crate) fn parse_rational(number: &str) -> Option<BigRational> {
+    let (mantissa, exp) = match number.split_once(['e', 'E']) {
+        Some((mantissa, exp)) => (mantissa, exp.parse::<i32>().ok()?),
+        None => (number, 0),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int_part, frac_part);
+    let numer = match digits.as_str() {
+        "" | "-" | "+" => BigInt::zero(),
+        digits => digits.parse::<BigInt>().ok()?,
+    };
+    let exp = exp.checked_sub(frac_part.len() as i32)?;
+    if exp.unsigned_abs() > MAX_EXPONENT {
+        return None;
+    }
+    let scale: BigInt = Pow::pow(BigInt::from(10), exp.unsigned_abs());
+    Some(if exp >= 0 {
+        BigRational::from_integer(numer * scale)
+    } else {
+        BigRational::new(numer, scale)
+    })
+}
+
+impl ExactMeasurement {
+    pub fn new(value: BigRational, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    /// Makes exact measurement from the shortest decimal representation of the value, so `0.1` is exactly `1/10`.
+    pub fn from_measurement(measurement: &Measurement) -> Self {
+        let value = parse_rational(&format!("{:e}", measurement.value)).unwrap_or_default();
+        Self::new(value, measurement.unit)
+    }
+
+    pub fn to_measurement(&self) -> Measurement {
+        let value = format_rational(
+            &self.value,
+            &FormatOptions {
+                significant: Some(17),
+                ..Default::default()
+            },
+        );
+        Measurement::new(value.parse().unwrap_or(f64::NAN), self.unit)
+    }
+
+    /// Converts to the same base unit as `Measurement::to_base`.
+    /// Units without exact factor, like degrees, are converted as floats.
+    pub fn to_base(&self) -> Self {
+        let Some((factor, offset)) = definition(self.unit) else {
+            return Self::from_measurement(&self.to_measurement().to_base());
+        };
+        let base_unit = Measurement::new(0., self.unit).to_base().unit;
+        Self::new(&self.value * factor + offset, base_unit)
+    }
+
+    /// Converts to other unit.
+    pub fn to_other(&self, target_unit: Unit) -> Self {
+        let base = self.to_base();
+        let Some((factor, offset)) = definition(target_unit) else {
+            return Self::from_measurement(&base.to_measurement().to_other(target_unit));
+        };
+        Self::new((base.value - offset) / factor, target_unit)
+    }
+
+    /// Returns is the value an integer.
+    pub fn is_integer(&self) -> bool {
+        self.value.denom().is_one()
+    }
+
+    /// Returns String formated value and unit using given format options.
+    pub fn txt_with(&self, options: &FormatOptions) -> String {
+        let name = format!("{:?}", self.unit);
+        options.with_name(format_rational(&self.value, options), &name, &name)
+    }
+
+    pub fn txt(&self) -> String {
+        self.txt_with(&FormatOptions::default())
+    }
+
+    /// Parses measurement like `1,5km` keeping all digits of the number.
+    pub fn parse(query: &str, locale: &Locale) -> Result<Self> {
+        let (number, len) = locale
+            .normalize_prefix(query)
+            .unwrap_or(("0".to_string(), 0));
+        let unit_part = query[len..].trim();
+        let Some(value) = parse_rational(&number) else {
+            let message = "Invalid conversion query: error parsing number part.";
+            return Err(ParseError::new(message, 0..len).into());
+        };
+        match Unit::from_symbol(unit_part) {
+            Some(unit) if definition(unit).is_none() => {
+                let message = "Invalid conversion query: the unit has no exact factor.";
+                Err(ParseError::new(message, len..query.len()).into())
+            }
+            Some(unit) => Ok(Self::new(value, unit)),
+            None => Err(Error::UnknownUnit(unit_error(query, len))),
+        }
+    }
+
+    /// Converts String query like `1 acre to m2 to acre` exactly.
+    pub fn convert(query: &str) -> Result<Self> {
+        Self::convert_with(query, &Locale::default())
+    }
+
+    /// Same as `convert`, but numbers are read using separators of given locale.
+    pub fn convert_with(query: &str, locale: &Locale) -> Result<Self> {
+        let regex = Regex::new(r"(:|to)").unwrap();
+        let parts: Vec<&str> = regex.split(query).collect();
+        let offsets = part_offsets(&regex, query);
+        if parts.len() < 2 {
+            return Err(Error::InvalidQuery(
+                "Invalid conversion query: error parsing parts.".to_string(),
+            ));
+        }
+        let from_part = parts[0].split_whitespace().collect::<String>();
+        let mut result = match Self::parse(&from_part, locale) {
+            Ok(m) => m,
+            Err(e) => {
+                let message = "Invalid conversion query: error parsing from part.";
+                return Err(part_error(e, message, parts[0], 0));
+            }
+        };
+        for (part, offset) in parts[1..].iter().zip(&offsets[1..]) {
+            let to_part = part.split_whitespace().collect::<String>();
+            let to = match Self::parse(&to_part, locale) {
+                Ok(m) => m,
+                Err(e) => {
+                    let message = "Invalid conversion query: error parsing to part";
+                    return Err(part_error(e, message, part, *offset));
+                }
+            };
+            if result.to_base().unit != to.to_base().unit {
+                return Err(Error::IncompatibleUnits(
+                    "Cannot convert measurements with different units.".to_string(),
+                ));
+            }
+            result = result.to_other(to.unit);
+        }
+        Ok(result)
+    }
+}
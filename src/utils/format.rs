@@ -0,0 +1,389 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, Zero};
+
+use super::{
+    i18n::{localized_name, Language},
+    locale::Locale,
+};
+
+/// Number of significant digits used for floating point results when nothing else is requested.
+/// It's enough to hide binary noise like `0.30000000000000004` while keeping real precision.
+pub const DEFAULT_SIGNIFICANT: usize = 15;
+
+/// How the number is written.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Notation {
+    /// Positional for usual magnitudes, scientific for very big or very small floats.
+    #[default]
+    Auto,
+    /// Always positional: `1234.5`, `0.00012`.
+    Standard,
+    /// `1.2345e3`.
+    Scientific,
+    /// Like scientific, but the exponent is always a multiple of 3: `12.345e3`.
+    Engineering,
+}
+
+/// How digits that don't fit are rounded.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Rounding {
+    /// Half away from zero: `2.5 -> 3`, `-2.5 -> -3`.
+    #[default]
+    HalfUp,
+    /// Half towards zero: `2.5 -> 2`.
+    HalfDown,
+    /// Half to the nearest even digit (banker's rounding): `2.5 -> 2`, `3.5 -> 4`.
+    HalfEven,
+    /// Away from zero.
+    Up,
+    /// Towards zero (truncation).
+    Down,
+    /// Towards positive infinity.
+    Ceiling,
+    /// Towards negative infinity.
+    Floor,
+}
+
+/// Options that control how numeric results are printed.
+/// `Default` gives up to 15 significant digits for floats, trimmed trailing zeros and half-up rounding.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FormatOptions {
+    /// Fixed count of digits after the decimal point (of the mantissa in scientific notations).
+    /// Takes precedence over `significant`.
+    pub decimals: Option<usize>,
+    /// Count of significant digits.
+    pub significant: Option<usize>,
+    pub notation: Notation,
+    /// Remove trailing zeros of the fractional part (`2.50 -> 2.5`, `3.00 -> 3`).
+    pub trim_zeros: bool,
+    pub rounding: Rounding,
+    /// Separators used to print numbers (and to read them in queries).
+    pub locale: Locale,
+    /// Separate thousands of the integer part with the locale grouping separator.
+    pub group_digits: bool,
+    /// Language of unit and currency names. `None` prints plain unit names like `Kilometer`.
+    pub language: Option<Language>,
+    /// Print currencies with their signs, like `$1,234.56` or `1 234,56 ₴`, instead of codes.
+    pub symbols: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            decimals: None,
+            significant: None,
+            notation: Notation::Auto,
+            trim_zeros: true,
+            rounding: Rounding::HalfUp,
+            locale: Locale::default(),
+            group_digits: false,
+            language: None,
+            symbols: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options with names in given language and separators of its locale.
+    pub fn localized(language: Language) -> Self {
+        Self {
+            locale: Locale::from_tag(language.code()).unwrap_or_default(),
+            language: Some(language),
+            ..Self::default()
+        }
+    }
+
+    /// Joins formatted number and name of the unit or currency, translating the name if language is set.
+    pub(crate) fn with_name(&self, number: String, key: &str, fallback: &str) -> String {
+        let name = self
+            .language
+            .and_then(|language| localized_name(key, &number, self.locale.decimal, language))
+            .unwrap_or_else(|| fallback.to_string());
+        format!("{} {}", number, name)
+    }
+}
+
+/// Decimal number as a list of digits: `0.d1d2d3... * 10^(exp + 1)`, so `exp` is the exponent of the first digit.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Digits {
+    pub negative: bool,
+    pub digits: Vec<u8>,
+    pub exp: i64,
+}
+
+impl Digits {
+    /// Shortest digits that round-trip to the same `f64`.
+    pub fn from_f64(value: f64) -> Self {
+        let repr = format!("{:e}", value.abs());
+        let (mantissa, exp) = repr.split_once('e').unwrap_or((&repr, "0"));
+        let exp = exp.parse::<i64>().unwrap_or(0);
+        let digits = mantissa
+            .bytes()
+            .filter(u8::is_ascii_digit)
+            .map(|b| b - b'0')
+            .collect();
+        Self::normalized(value.is_sign_negative(), digits, exp)
+    }
+
+    /// Decimal digits of a rational number. Generation stops when the expansion terminates,
+    /// or when both `significant` digits and `scale` fractional digits are known. In that case
+    /// a trailing non-zero digit marks the remainder for rounding and `false` is returned.
+    pub fn from_rational(value: &BigRational, significant: usize, scale: usize) -> (Self, bool) {
+        let denom = value.denom().abs();
+        let numer = value.numer().abs();
+        let ten = BigInt::from(10);
+        let int = &numer / &denom;
+        let mut rem = &numer % &denom;
+        let mut digits: Vec<u8> = if int.is_zero() {
+            Vec::new()
+        } else {
+            int.to_string().bytes().map(|b| b - b'0').collect()
+        };
+        let mut exp = digits.len() as i64 - 1;
+        let mut frac = 0;
+        while !rem.is_zero() && (digits.len() < significant || frac < scale) {
+            rem *= &ten;
+            let digit = (&rem / &denom).to_string().parse::<u8>().unwrap_or(0);
+            rem %= &denom;
+            frac += 1;
+            if digits.is_empty() && digit == 0 {
+                continue;
+            }
+            digits.push(digit);
+        }
+        if int.is_zero() {
+            exp = -(frac as i64) + digits.len() as i64 - 1;
+        }
+        let exact = rem.is_zero();
+        if !exact {
+            digits.push(1);
+        }
+        (Self::normalized(value.is_negative(), digits, exp), exact)
+    }
+
+    fn normalized(negative: bool, mut digits: Vec<u8>, exp: i64) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        if digits.is_empty() {
+            return Self {
+                negative: false,
+                digits,
+                exp: 0,
+            };
+        }
+        Self {
+            negative,
+            digits,
+            exp,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Keeps `keep` leading digits and rounds the rest away.
+    pub fn round(&self, keep: i64, mode: Rounding) -> Self {
+        if keep >= self.digits.len() as i64 {
+            return self.clone();
+        }
+        let kept: Vec<u8> = self.digits[..keep.max(0) as usize].to_vec();
+        let (first, rest_nonzero) = if keep < 0 {
+            (0, true)
+        } else {
+            let dropped = &self.digits[keep as usize..];
+            (dropped[0], dropped[1..].iter().any(|d| *d != 0))
+        };
+        let half = match first {
+            5 if !rest_nonzero => std::cmp::Ordering::Equal,
+            d if d >= 5 => std::cmp::Ordering::Greater,
+            _ => std::cmp::Ordering::Less,
+        };
+        let last_odd = kept.last().map(|d| d % 2 == 1).unwrap_or(false);
+        let increment = match mode {
+            Rounding::Down => false,
+            Rounding::Up => true,
+            Rounding::Ceiling => !self.negative,
+            Rounding::Floor => self.negative,
+            Rounding::HalfUp => half != std::cmp::Ordering::Less,
+            Rounding::HalfDown => half == std::cmp::Ordering::Greater,
+            Rounding::HalfEven => {
+                half == std::cmp::Ordering::Greater
+                    || (half == std::cmp::Ordering::Equal && last_odd)
+            }
+        };
+        if !increment {
+            return Self::normalized(self.negative, kept, self.exp);
+        }
+        if keep <= 0 {
+            // Everything was dropped, the result is one unit at the rounding position.
+            return Self::normalized(self.negative, vec![1], self.exp - keep + 1);
+        }
+        let mut kept = kept;
+        let mut exp = self.exp;
+        let mut i = kept.len();
+        loop {
+            if i == 0 {
+                kept.insert(0, 1);
+                exp += 1;
+                break;
+            }
+            i -= 1;
+            if kept[i] == 9 {
+                kept[i] = 0;
+            } else {
+                kept[i] += 1;
+                break;
+            }
+        }
+        Self::normalized(self.negative, kept, exp)
+    }
+
+    /// Rounds to `scale` digits after the decimal point.
+    pub fn round_to_scale(&self, scale: i64, mode: Rounding) -> Self {
+        if self.is_zero() {
+            return self.clone();
+        }
+        self.round(self.exp + 1 + scale, mode)
+    }
+}
+
+/// Formats a floating point number.
+pub fn format_number(value: f64, options: &FormatOptions) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0. { "inf" } else { "-inf" }.to_string();
+    }
+    format_digits(&Digits::from_f64(value), options, false)
+}
+
+/// Formats a rational number, printing it exactly if the decimal expansion terminates and no precision is requested.
+pub fn format_rational(value: &BigRational, options: &FormatOptions) -> String {
+    let significant = options.significant.unwrap_or(DEFAULT_SIGNIFICANT) + 4;
+    let scale = options.decimals.map(|d| d + 4).unwrap_or(0);
+    // Terminating expansions are printed in full, but only up to a sane length.
+    let (digits, exact) = Digits::from_rational(value, significant.max(64), scale);
+    if exact {
+        return format_digits(&digits, options, true);
+    }
+    let (digits, _) = Digits::from_rational(value, significant, scale);
+    format_digits(&digits, options, false)
+}
+
+/// Formats a number given as digits. `exact` numbers are not cut to the default precision.
+pub(crate) fn format_digits(digits: &Digits, options: &FormatOptions, exact: bool) -> String {
+    let notation = match options.notation {
+        Notation::Auto if !exact && !digits.is_zero() && (digits.exp >= 21 || digits.exp < -7) => {
+            Notation::Scientific
+        }
+        Notation::Auto => Notation::Standard,
+        n => n,
+    };
+    // Exponent shown after the mantissa, recomputed because rounding can carry into a new digit.
+    let shown_exp = |d: &Digits| match notation {
+        Notation::Scientific => d.exp,
+        Notation::Engineering => d.exp.div_euclid(3) * 3,
+        _ => 0,
+    };
+    let round = |exp_shift: i64| -> Digits {
+        if let Some(decimals) = options.decimals {
+            digits.round_to_scale(decimals as i64 - exp_shift, options.rounding)
+        } else if let Some(significant) = options.significant {
+            digits.round(significant as i64, options.rounding)
+        } else if exact {
+            digits.clone()
+        } else {
+            digits.round(DEFAULT_SIGNIFICANT as i64, options.rounding)
+        }
+    };
+    let mut rounded = round(shown_exp(digits));
+    if shown_exp(&rounded) != shown_exp(digits) {
+        rounded = round(shown_exp(&rounded));
+    }
+    let exp_shift = if rounded.is_zero() {
+        0
+    } else {
+        shown_exp(&rounded)
+    };
+
+    let mut min_frac = 0;
+    if !options.trim_zeros {
+        if let Some(decimals) = options.decimals {
+            min_frac = decimals;
+        } else if let Some(significant) = options.significant {
+            let int_digits = if rounded.is_zero() {
+                1
+            } else {
+                (rounded.exp - exp_shift + 1).max(1)
+            };
+            min_frac = (significant as i64 - int_digits).max(0) as usize;
+        }
+    }
+    let mantissa = positional(&rounded, exp_shift, min_frac, options);
+    let sign = if rounded.negative && !rounded.is_zero() {
+        "-"
+    } else {
+        ""
+    };
+    match notation {
+        Notation::Scientific | Notation::Engineering => {
+            format!("{}{}e{}", sign, mantissa, exp_shift)
+        }
+        _ => format!("{}{}", sign, mantissa),
+    }
+}
+
+/// Writes digits positionally after dividing by `10^exp_shift`.
+fn positional(d: &Digits, exp_shift: i64, min_frac: usize, options: &FormatOptions) -> String {
+    let exp = d.exp - exp_shift;
+    let digit = |i: i64| -> char {
+        if i >= 0 && (i as usize) < d.digits.len() {
+            (b'0' + d.digits[i as usize]) as char
+        } else {
+            '0'
+        }
+    };
+    let mut int: String = if d.is_zero() || exp < 0 {
+        "0".to_string()
+    } else {
+        (0..=exp).map(digit).collect()
+    };
+    if let (true, Some(separator)) = (options.group_digits, options.locale.grouping) {
+        int = group(&int, separator);
+    }
+    let frac_len = if d.is_zero() {
+        0
+    } else {
+        (d.digits.len() as i64 - exp - 1).max(0)
+    };
+    let mut frac: String = (exp + 1..exp + 1 + frac_len).map(digit).collect();
+    while frac.len() < min_frac {
+        frac.push('0');
+    }
+    if frac.is_empty() {
+        int
+    } else {
+        format!("{}{}{}", int, options.locale.decimal, frac)
+    }
+}
+
+/// Inserts separator between every three digits from the right.
+pub(crate) fn group(int: &str, separator: char) -> String {
+    let len = int.chars().count();
+    let mut grouped = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (len - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(c);
+    }
+    grouped
+}
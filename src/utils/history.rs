@@ -0,0 +1,262 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use regex::Regex;
+
+use super::rates::{from_base, BoxFuture, RateProvider, RateTable};
+use crate::{Error, Result};
+
+/// Days after the last known rates that still use them, covering weekends and holidays.
+const MAX_GAP_DAYS: i64 = 7;
+
+/// Calendar date like `2024-03-01`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Returns the date if it exists, `None` for dates like `2023-02-29`.
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days)
+            .contains(&day)
+            .then_some(Self { year, month, day })
+    }
+
+    /// Returns count of days since 1970-01-01.
+    pub fn days(&self) -> i64 {
+        // Days from civil date, with years starting in March so the leap day is the last one.
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = (self.month as i64 + 9) % 12;
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    /// Returns Unix time of the start of the day in UTC.
+    pub fn unix(&self) -> u64 {
+        (self.days() * 86400).max(0) as u64
+    }
+}
+
+impl FromStr for Date {
+    type Err = Error;
+
+    /// Reads date in ISO 8601 form, `YYYY-MM-DD`.
+    fn from_str(text: &str) -> Result<Self> {
+        let invalid = || Error::InvalidQuery(format!("Invalid date '{}'.", text));
+        let mut parts = text.trim().splitn(3, '-');
+        let mut next = || parts.next().and_then(|part| part.parse().ok());
+        let (Some(year), Some(month), Some(day)) = (next(), next(), next()) else {
+            return Err(invalid());
+        };
+        Date::new(year as i32, month, day).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Source of exchange rates of past days.
+pub trait HistoricalRates: Send + Sync {
+    /// Returns rates from the base currency published on the date, or on the last business day before it.
+    fn rates_at<'a>(&'a self, base: &'a str, date: Date) -> BoxFuture<'a, Result<RateTable>>;
+}
+
+/// Rates of many days, given directly or read from a CSV file. Works without network.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    tables: BTreeMap<Date, RateTable>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds rates of the date, `updated` of the table is set to the date.
+    pub fn insert(&mut self, date: Date, table: RateTable) {
+        let table = RateTable {
+            updated: Some(date.unix()),
+            ..table
+        };
+        self.tables.insert(date, table);
+    }
+
+    /// Returns rates of the date, or of the last day before it within a week.
+    pub fn table(&self, date: Date) -> Option<&RateTable> {
+        let (day, table) = self.tables.range(..=date).next_back()?;
+        (date.days() - day.days() <= MAX_GAP_DAYS).then_some(table)
+    }
+
+    /// Returns first and last dates with rates.
+    pub fn range(&self) -> Option<(Date, Date)> {
+        Some((
+            *self.tables.keys().next()?,
+            *self.tables.keys().next_back()?,
+        ))
+    }
+
+    /// Reads CSV of rates from the base currency, in the format of the ECB history file:
+    /// `Date,USD,JPY,…` header and rows like `2024-03-01,1.0830,162.12,…`.
+    /// Empty and `N/A` cells are skipped.
+    pub fn from_csv(csv: &str, base: &str) -> Result<Self> {
+        let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+        let Some(header) = lines.next() else {
+            return Err(Error::Provider("the CSV has no header.".to_string()));
+        };
+        let codes: Vec<&str> = header.split(',').map(str::trim).collect();
+        let mut history = History::new();
+        for line in lines {
+            let mut cells = line.split(',').map(str::trim);
+            let date: Date = cells.next().unwrap_or_default().parse()?;
+            let rates: HashMap<String, f64> = codes[1..]
+                .iter()
+                .zip(cells)
+                .filter(|(code, _)| !code.is_empty())
+                .filter_map(|(code, rate)| Some((code.to_string(), rate.parse().ok()?)))
+                .collect();
+            history.insert(date, RateTable::new(base, rates));
+        }
+        if history.tables.is_empty() {
+            return Err(Error::Provider("the CSV has no rates.".to_string()));
+        }
+        Ok(history)
+    }
+
+    /// Reads CSV file in the format of `from_csv`.
+    pub fn from_csv_file(path: impl AsRef<Path>, base: &str) -> Result<Self> {
+        match std::fs::read_to_string(path.as_ref()) {
+            Ok(csv) => Self::from_csv(&csv, base),
+            Err(e) => Err(Error::Provider(format!(
+                "can't read {}: {}.",
+                path.as_ref().display(),
+                e
+            ))),
+        }
+    }
+
+    /// Reads the ECB history file `eurofxref-hist.xml`, rates of every business day since 1999.
+    pub fn from_ecb_xml(xml: &str) -> Result<Self> {
+        let regex = Regex::new(r#"time\s*=\s*['"](\d{4}-\d{2}-\d{2})['"]"#).unwrap();
+        let days: Vec<_> = regex.captures_iter(xml).collect();
+        let mut history = History::new();
+        for (i, caps) in days.iter().enumerate() {
+            let start = caps.get(0).unwrap().end();
+            let end = days
+                .get(i + 1)
+                .map_or(xml.len(), |next| next.get(0).unwrap().start());
+            if let Ok(table) = RateTable::from_ecb_xml(&xml[start..end]) {
+                history.insert(caps[1].parse()?, table);
+            }
+        }
+        if history.tables.is_empty() {
+            return Err(Error::Provider("the response has no rates.".to_string()));
+        }
+        Ok(history)
+    }
+
+    fn rates(&self, base: &str, date: Date) -> Result<RateTable> {
+        match self.table(date) {
+            Some(table) => from_base(table.clone(), base),
+            None => Err(Error::Provider(format!("no rates for {}.", date))),
+        }
+    }
+}
+
+impl HistoricalRates for History {
+    fn rates_at<'a>(&'a self, base: &'a str, date: Date) -> BoxFuture<'a, Result<RateTable>> {
+        Box::pin(async move { self.rates(base, date) })
+    }
+}
+
+/// Reference rates of the European Central Bank since 1999. The history file is downloaded
+/// on the first request and kept in memory.
+pub struct EcbHistory {
+    base_url: String,
+    client: reqwest::Client,
+    history: Mutex<Option<Arc<History>>>,
+}
+
+impl Default for EcbHistory {
+    fn default() -> Self {
+        Self {
+            base_url: "https://www.ecb.europa.eu/stats/eurofxref".to_string(),
+            client: reqwest::Client::new(),
+            history: Mutex::new(None),
+        }
+    }
+}
+
+impl EcbHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses other server, rates are read from `{url}/eurofxref-hist.xml`.
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Uses given client, with its proxy, timeouts and user agent.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    async fn history(&self) -> Result<Arc<History>> {
+        if let Some(history) = self.history.lock().unwrap().clone() {
+            return Ok(history);
+        }
+        let xml = self
+            .client
+            .get(format!("{}/eurofxref-hist.xml", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let history = Arc::new(History::from_ecb_xml(&xml)?);
+        *self.history.lock().unwrap() = Some(history.clone());
+        Ok(history)
+    }
+}
+
+impl HistoricalRates for EcbHistory {
+    fn rates_at<'a>(&'a self, base: &'a str, date: Date) -> BoxFuture<'a, Result<RateTable>> {
+        Box::pin(async move { self.history().await?.rates(base, date) })
+    }
+}
+
+/// Rates of one date, so conversions of a query with a date use the usual provider interface.
+pub(crate) struct RatesAt {
+    pub history: Arc<dyn HistoricalRates>,
+    pub date: Date,
+}
+
+impl RateProvider for RatesAt {
+    fn rates<'a>(&'a self, base: &'a str) -> BoxFuture<'a, Result<RateTable>> {
+        self.history.rates_at(base, self.date)
+    }
+}
@@ -0,0 +1,113 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+/// Message catalog with names of units and currencies, embedded into the library.
+const CATALOG: &str = include_str!("i18n/catalog.tsv");
+
+/// Language of unit and currency names in results.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Language {
+    English,
+    German,
+    Spanish,
+    French,
+    Russian,
+    Ukrainian,
+}
+
+impl Language {
+    /// Returns language by code like `en` or `uk-UA`.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let lang = code.split(['-', '_']).next().unwrap_or_default();
+        match lang.to_lowercase().as_str() {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            "es" => Some(Language::Spanish),
+            "fr" => Some(Language::French),
+            "ru" => Some(Language::Russian),
+            "uk" | "ua" => Some(Language::Ukrainian),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+            Language::Spanish => "es",
+            Language::French => "fr",
+            Language::Russian => "ru",
+            Language::Ukrainian => "uk",
+        }
+    }
+
+    /// Returns index of the plural form in the catalog for the number as it's printed.
+    fn plural_form(&self, number: &str, decimal: char) -> usize {
+        let number = number.trim_start_matches('-');
+        let scientific = number.contains('e');
+        let (int, frac) = number.split_once(decimal).unwrap_or((number, ""));
+        let int: String = int.chars().filter(char::is_ascii_digit).collect();
+        let int = int.trim_start_matches('0');
+        let fraction = !frac.is_empty();
+        // Only the last two digits matter for the Slavic rules.
+        let tail = int[int.len().saturating_sub(2)..]
+            .parse::<u32>()
+            .unwrap_or(0);
+        match self {
+            Language::English | Language::German => (scientific || fraction || int != "1") as usize,
+            Language::Spanish => {
+                (scientific || int != "1" || frac.chars().any(|c| c != '0')) as usize
+            }
+            Language::French => (scientific || int.len() > 1 || tail > 1) as usize,
+            Language::Russian | Language::Ukrainian => {
+                if scientific {
+                    2
+                } else if fraction {
+                    3
+                } else if tail % 10 == 1 && tail != 11 {
+                    0
+                } else if (2..=4).contains(&(tail % 10)) && !(12..=14).contains(&tail) {
+                    1
+                } else {
+                    2
+                }
+            }
+        }
+    }
+}
+
+fn catalog() -> &'static HashMap<(String, Language), Vec<String>> {
+    static MESSAGES: OnceLock<HashMap<(String, Language), Vec<String>>> = OnceLock::new();
+    MESSAGES.get_or_init(|| {
+        CATALOG
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .filter_map(|line| {
+                let mut cols = line.split('\t');
+                let key = cols.next()?;
+                let language = Language::from_code(cols.next()?)?;
+                let forms = cols.next()?.split('|').map(str::to_string).collect();
+                Some(((key.to_string(), language), forms))
+            })
+            .collect()
+    })
+}
+
+/// Returns localized name by catalog key (unit name like `Kilometer` or currency code like `USD`)
+/// in the plural form matching the printed number, falling back to English.
+/// `decimal` is the decimal separator used in `number`.
+pub fn localized_name(
+    key: &str,
+    number: &str,
+    decimal: char,
+    language: Language,
+) -> Option<String> {
+    let (forms, language) = match catalog().get(&(key.to_string(), language)) {
+        Some(forms) => (forms, language),
+        None => (
+            catalog().get(&(key.to_string(), Language::English))?,
+            Language::English,
+        ),
+    };
+    let form = language.plural_form(number, decimal).min(forms.len() - 1);
+    forms.get(form).cloned()
+}
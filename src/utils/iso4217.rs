@@ -0,0 +1,106 @@
+use std::sync::OnceLock;
+
+/// Table of currencies, embedded into the library.
+const TABLE: &str = include_str!("iso4217/currencies.tsv");
+
+/// Currency of the ISO 4217 registry.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CurrencyInfo {
+    /// Alphabetic code like `USD`.
+    pub code: &'static str,
+    /// Numeric code like `840`, `0` for currencies without one.
+    pub numeric: u16,
+    /// Count of digits after the decimal point of the minor unit, like `2` for cents.
+    pub minor_units: u8,
+    /// Sign like `$`, if the currency has one.
+    pub symbol: Option<&'static str>,
+    /// English name like `US dollar`.
+    pub name: &'static str,
+    /// Other names used in queries, like `bucks` or `грн`.
+    pub aliases: Vec<&'static str>,
+}
+
+impl CurrencyInfo {
+    /// Returns the sign of the currency, or its code if it has no sign.
+    pub fn sign(&self) -> &'static str {
+        self.symbol.unwrap_or(self.code)
+    }
+}
+
+/// Returns all known currencies, sorted by code.
+pub fn currencies() -> &'static [CurrencyInfo] {
+    static CURRENCIES: OnceLock<Vec<CurrencyInfo>> = OnceLock::new();
+    CURRENCIES.get_or_init(|| {
+        TABLE
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .filter_map(|line| {
+                let mut cols = line.split('\t');
+                let code = cols.next()?;
+                let numeric = cols.next()?.parse().ok()?;
+                let minor_units = cols.next()?.parse().ok()?;
+                let symbol = Some(cols.next()?).filter(|s| *s != "-");
+                let name = cols.next()?;
+                let aliases = match cols.next()? {
+                    "-" => Vec::new(),
+                    aliases => aliases.split('|').collect(),
+                };
+                Some(CurrencyInfo {
+                    code,
+                    numeric,
+                    minor_units,
+                    symbol,
+                    name,
+                    aliases,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Returns currency by its alphabetic code, case-insensitive.
+pub fn find(code: &str) -> Option<&'static CurrencyInfo> {
+    currencies()
+        .iter()
+        .find(|c| c.code.eq_ignore_ascii_case(code))
+}
+
+/// Returns currency by its numeric code.
+pub fn find_numeric(numeric: u16) -> Option<&'static CurrencyInfo> {
+    currencies()
+        .iter()
+        .find(|c| c.numeric == numeric && numeric != 0)
+}
+
+/// Lowercase text without whitespace, so `US dollars` matches `usdollars` of a collapsed query.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<String>().to_lowercase()
+}
+
+/// Returns currency by its code, symbol or alias, like `usd`, `$`, `bucks` or `грн`.
+/// Symbols used by several currencies, like `kr`, are not recognized.
+pub fn lookup(text: &str) -> Option<&'static CurrencyInfo> {
+    if let Some(currency) = find(text.trim()) {
+        return Some(currency);
+    }
+    let text = normalize(text);
+    let mut by_symbol = currencies()
+        .iter()
+        .filter(|c| c.symbol.is_some_and(|s| normalize(s) == text));
+    if let (Some(currency), None) = (by_symbol.next(), by_symbol.next()) {
+        return Some(currency);
+    }
+    currencies()
+        .iter()
+        .find(|c| c.aliases.iter().any(|alias| normalize(alias) == text))
+}
+
+/// Returns all codes, symbols and aliases, for suggestions.
+pub(crate) fn names() -> impl Iterator<Item = &'static str> {
+    currencies().iter().flat_map(|c| {
+        [c.code]
+            .into_iter()
+            .chain(c.symbol)
+            .chain(c.aliases.iter().copied())
+    })
+}
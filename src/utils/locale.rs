@@ -0,0 +1,133 @@
+/// Decimal and grouping separators used to read and print numbers.
+/// Spaces (including no-break and thin ones) are always accepted as grouping separators in input.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Locale {
+    pub decimal: char,
+    pub grouping: Option<char>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::EN
+    }
+}
+
+const SPACES: [char; 3] = [' ', '\u{a0}', '\u{202f}'];
+
+impl Locale {
+    /// `1,234.5`
+    pub const EN: Locale = Locale::new('.', Some(','));
+    /// `1.234,5`
+    pub const DE: Locale = Locale::new(',', Some('.'));
+    /// `1 234,5` (narrow no-break space)
+    pub const FR: Locale = Locale::new(',', Some('\u{202f}'));
+    /// `1 234,5` (no-break space)
+    pub const RU: Locale = Locale::new(',', Some('\u{a0}'));
+    /// `1'234.5`
+    pub const CH: Locale = Locale::new('.', Some('\''));
+
+    pub const fn new(decimal: char, grouping: Option<char>) -> Self {
+        Self { decimal, grouping }
+    }
+
+    /// Returns locale by language tag like `en`, `de-AT` or `uk_UA`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let lang = tag
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let region = tag.split(['-', '_']).nth(1).map(|r| r.to_uppercase());
+        if region.as_deref() == Some("CH") {
+            return Some(Self::CH);
+        }
+        match lang.as_str() {
+            "en" | "ja" | "zh" | "ko" | "he" | "th" => Some(Self::EN),
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" => Some(Self::DE),
+            "fr" => Some(Self::FR),
+            "ru" | "uk" | "be" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "bg" | "kk" => {
+                Some(Self::RU)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_group_separator(&self, c: char) -> bool {
+        Some(c) == self.grouping || SPACES.contains(&c)
+    }
+
+    /// Parses a number at the start of text (sign, grouping, decimal part and exponent).
+    /// Returns the value and the count of consumed bytes.
+    pub fn parse_prefix(&self, text: &str) -> Option<(f64, usize)> {
+        let (normalized, len) = self.normalize_prefix(text)?;
+        Some((normalized.parse::<f64>().ok()?, len))
+    }
+
+    /// Reads a number at the start of text and rewrites it without grouping,
+    /// with `.` as decimal separator, like `-1234.5e-3`. Returns it and the count of consumed bytes.
+    pub(crate) fn normalize_prefix(&self, text: &str) -> Option<(String, usize)> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let at = |i: usize| chars.get(i).map(|(_, c)| *c);
+        let is_digit = |i: usize| at(i).is_some_and(|c| c.is_ascii_digit());
+        let mut normalized = String::new();
+        let mut i = 0;
+
+        match at(i) {
+            Some('-') | Some('−') => {
+                normalized.push('-');
+                i += 1;
+            }
+            Some('+') => i += 1,
+            _ => {}
+        }
+        let int_start = i;
+        while is_digit(i) {
+            normalized.push(at(i)?);
+            i += 1;
+            // Grouping separator must be followed by exactly three digits.
+            if at(i).is_some_and(|c| self.is_group_separator(c))
+                && (1..=3).all(|k| is_digit(i + k))
+                && !is_digit(i + 4)
+            {
+                i += 1;
+            }
+        }
+        let has_int = i > int_start;
+        if at(i) == Some(self.decimal) && is_digit(i + 1) {
+            normalized.push('.');
+            i += 1;
+            while is_digit(i) {
+                normalized.push(at(i)?);
+                i += 1;
+            }
+        } else if !has_int {
+            return None;
+        }
+        if let Some('e') | Some('E') = at(i) {
+            let sign = matches!(at(i + 1), Some('-') | Some('+') | Some('−'));
+            let first = if sign { i + 2 } else { i + 1 };
+            if is_digit(first) {
+                normalized.push('e');
+                if sign && at(i + 1) != Some('+') {
+                    normalized.push('-');
+                }
+                i = first;
+                while is_digit(i) {
+                    normalized.push(at(i)?);
+                    i += 1;
+                }
+            }
+        }
+        let len = chars.get(i).map(|(b, _)| *b).unwrap_or(text.len());
+        Some((normalized, len))
+    }
+
+    /// Parses whole text as a number.
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        let text = text.trim();
+        match self.parse_prefix(text) {
+            Some((value, len)) if len == text.len() => Some(value),
+            _ => None,
+        }
+    }
+}
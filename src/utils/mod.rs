@@ -1,4 +1,13 @@
+pub mod assets;
 pub mod calc;
 pub mod currency;
+pub mod diagnostic;
+pub mod exact;
+pub mod format;
+pub mod history;
+pub mod i18n;
+pub mod iso4217;
+pub mod locale;
+pub mod rates;
 pub mod translate;
 pub mod units;
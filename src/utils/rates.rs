@@ -0,0 +1,536 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::diagnostic::ParseError;
+use crate::{Error, Result};
+
+/// Future returned by providers, boxed so providers can be used as trait objects.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Time to keep rates when the provider doesn't tell when they are updated.
+const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Returns current Unix time in seconds.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Exchange rates from one base currency: `1 base = rates[code] code`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RateTable {
+    pub base: String,
+    pub rates: HashMap<String, f64>,
+    /// Unix time when the provider published the rates, if it tells.
+    pub updated: Option<u64>,
+    /// Unix time when the provider publishes new rates, if it tells.
+    pub next_update: Option<u64>,
+    /// Unix time when the rates were received.
+    pub fetched: u64,
+}
+
+impl RateTable {
+    pub fn new(base: &str, rates: HashMap<String, f64>) -> Self {
+        let rates = rates
+            .into_iter()
+            .map(|(code, rate)| (code.to_uppercase(), rate))
+            .collect();
+        Self {
+            base: base.to_uppercase(),
+            rates,
+            updated: None,
+            next_update: None,
+            fetched: now(),
+        }
+    }
+
+    /// Returns how old the rates are, from their publication or from receiving them.
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.updated.unwrap_or(self.fetched)))
+    }
+
+    /// Returns are the rates still actual: before the next update of the provider,
+    /// or within `ttl` after receiving them if the provider doesn't tell it.
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        let expires = self.next_update.unwrap_or(self.fetched + ttl.as_secs());
+        now() < expires
+    }
+
+    /// Returns how much of the currency is one base currency.
+    pub fn rate(&self, code: &str) -> Option<f64> {
+        let code = code.to_uppercase();
+        if code == self.base {
+            return Some(1.);
+        }
+        self.rates.get(&code).copied()
+    }
+
+    /// Returns how much of `to` is one `from`, as `rate(base→to) / rate(base→from)`.
+    pub fn cross_rate(&self, from: &str, to: &str) -> Option<f64> {
+        if from.eq_ignore_ascii_case(to) {
+            return Some(1.);
+        }
+        let from = self.rate(from).filter(|rate| *rate != 0.)?;
+        Some(self.rate(to)? / from)
+    }
+
+    /// Returns the same rates from other base, like EUR rates from USD ones.
+    pub fn rebase(&self, base: &str) -> Option<RateTable> {
+        let factor = self.rate(base)?;
+        if factor == 0. {
+            return None;
+        }
+        let rates = self
+            .rates
+            .iter()
+            .map(|(code, rate)| (code.clone(), rate / factor))
+            .chain([(self.base.clone(), 1. / factor)])
+            .filter(|(code, _)| !code.eq_ignore_ascii_case(base))
+            .collect();
+        Some(RateTable {
+            updated: self.updated,
+            next_update: self.next_update,
+            fetched: self.fetched,
+            ..RateTable::new(base, rates)
+        })
+    }
+
+    /// Adds rates of the other table that this one doesn't have, like crypto rates to fiat ones.
+    pub fn merge(&mut self, other: &RateTable) {
+        let Some(other) = other.rebase(&self.base) else {
+            return;
+        };
+        for (code, rate) in other.rates {
+            self.rates.entry(code).or_insert(rate);
+        }
+    }
+
+    /// Reads rates in the format of open.er-api.com, `{"base_code": "USD", "rates": {…}}`.
+    /// `base` can be used instead of `base_code`, times are read from `time_last_update_unix`,
+    /// `time_next_update_unix` and `time_fetched_unix` if they are given.
+    pub fn from_json(json: &str) -> Result<RateTable> {
+        let value: Value = match serde_json::from_str(json) {
+            Ok(value) => value,
+            Err(e) => return Err(Error::Provider(format!("invalid rates JSON: {}.", e))),
+        };
+        Self::from_value(&value)
+    }
+
+    fn from_value(value: &Value) -> Result<RateTable> {
+        let Some(base) = value["base_code"].as_str().or(value["base"].as_str()) else {
+            return Err(Error::Provider(
+                "the response has no base currency.".to_string(),
+            ));
+        };
+        let Some(rates) = value["rates"].as_object() else {
+            return Err(Error::Provider("the response has no rates.".to_string()));
+        };
+        let rates = rates
+            .iter()
+            .filter_map(|(code, rate)| Some((code.clone(), rate.as_f64()?)))
+            .collect();
+        let time = |key: &str| value[key].as_u64();
+        let table = RateTable::new(base, rates);
+        Ok(RateTable {
+            updated: time("time_last_update_unix"),
+            next_update: time("time_next_update_unix"),
+            fetched: time("time_fetched_unix").unwrap_or(table.fetched),
+            ..table
+        })
+    }
+
+    /// Writes rates in the format read by `from_json`.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "base_code": self.base,
+            "rates": self.rates,
+            "time_last_update_unix": self.updated,
+            "time_next_update_unix": self.next_update,
+            "time_fetched_unix": self.fetched,
+        })
+        .to_string()
+    }
+
+    /// Reads daily reference rates of the European Central Bank, they are EUR based.
+    pub fn from_ecb_xml(xml: &str) -> Result<RateTable> {
+        let regex =
+            Regex::new(r#"currency\s*=\s*['"]([A-Z]{3})['"]\s+rate\s*=\s*['"]([0-9.]+)['"]"#)
+                .unwrap();
+        let rates: HashMap<String, f64> = regex
+            .captures_iter(xml)
+            .filter_map(|caps| Some((caps[1].to_string(), caps[2].parse().ok()?)))
+            .collect();
+        if rates.is_empty() {
+            return Err(Error::Provider("the response has no rates.".to_string()));
+        }
+        Ok(RateTable::new("EUR", rates))
+    }
+}
+
+/// Source of exchange rates, like a web service or a file.
+pub trait RateProvider: Send + Sync {
+    /// Returns rates from the base currency.
+    fn rates<'a>(&'a self, base: &'a str) -> BoxFuture<'a, Result<RateTable>>;
+}
+
+/// Returns the table from given base, providers with one fixed base give cross rates.
+pub(crate) fn from_base(table: RateTable, base: &str) -> Result<RateTable> {
+    match table.rebase(base) {
+        Some(table) => Ok(table),
+        None => Err(Error::Provider(format!(
+            "no rates for {}.",
+            base.to_uppercase()
+        ))),
+    }
+}
+
+/// Rates of open.er-api.com, updated daily, no key needed.
+#[derive(Debug, Clone)]
+pub struct OpenErApi {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl Default for OpenErApi {
+    fn default() -> Self {
+        Self {
+            base_url: "https://open.er-api.com/v6".to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl OpenErApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses other server with the same API, like a mirror: rates are read from `{url}/latest/{BASE}`.
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Uses given client, with its proxy, timeouts and user agent.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+impl RateProvider for OpenErApi {
+    fn rates<'a>(&'a self, base: &'a str) -> BoxFuture<'a, Result<RateTable>> {
+        Box::pin(async move {
+            let url = format!("{}/latest/{}", self.base_url, base.to_uppercase());
+            let response = self
+                .client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Value>()
+                .await?;
+            RateTable::from_value(&response)
+        })
+    }
+}
+
+/// Daily reference rates of the European Central Bank, about 30 currencies.
+#[derive(Debug, Clone)]
+pub struct Ecb {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl Default for Ecb {
+    fn default() -> Self {
+        Self {
+            base_url: "https://www.ecb.europa.eu/stats/eurofxref".to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Ecb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses other server, rates are read from `{url}/eurofxref-daily.xml`.
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Uses given client, with its proxy, timeouts and user agent.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+impl RateProvider for Ecb {
+    fn rates<'a>(&'a self, base: &'a str) -> BoxFuture<'a, Result<RateTable>> {
+        Box::pin(async move {
+            let xml = self
+                .client
+                .get(format!("{}/eurofxref-daily.xml", self.base_url))
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            from_base(RateTable::from_ecb_xml(&xml)?, base)
+        })
+    }
+}
+
+/// Prices of bitcoin, ether, gold and silver from CoinGecko, no key needed.
+#[derive(Debug, Clone)]
+pub struct CoinGecko {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl Default for CoinGecko {
+    fn default() -> Self {
+        Self {
+            base_url: "https://api.coingecko.com/api/v3".to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl CoinGecko {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses other server with the same API, prices are read from `{url}/simple/price`.
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Uses given client, with its proxy, timeouts and user agent.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Reads response like `{"bitcoin": {"usd": 65000, "xau": 30}, "ethereum": {"usd": 3000}}`.
+    /// Metals are priced through bitcoin, as the API gives prices in them only.
+    fn table(value: &Value) -> Result<RateTable> {
+        let price = |id: &str, currency: &str| value[id][currency].as_f64().filter(|p| *p > 0.);
+        let Some(bitcoin) = price("bitcoin", "usd") else {
+            return Err(Error::Provider(
+                "the response has no price of BTC.".to_string(),
+            ));
+        };
+        let mut rates = HashMap::from([("BTC".to_string(), 1. / bitcoin)]);
+        if let Some(ether) = price("ethereum", "usd") {
+            rates.insert("ETH".to_string(), 1. / ether);
+        }
+        for metal in ["xau", "xag"] {
+            if let Some(in_metal) = price("bitcoin", metal) {
+                rates.insert(metal.to_uppercase(), in_metal / bitcoin);
+            }
+        }
+        Ok(RateTable::new("USD", rates))
+    }
+}
+
+impl RateProvider for CoinGecko {
+    fn rates<'a>(&'a self, base: &'a str) -> BoxFuture<'a, Result<RateTable>> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get(format!("{}/simple/price", self.base_url))
+                .query(&[
+                    ("ids", "bitcoin,ethereum"),
+                    ("vs_currencies", "usd,xau,xag"),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Value>()
+                .await?;
+            from_base(CoinGecko::table(&response)?, base)
+        })
+    }
+}
+
+/// Fixed rates, given directly or read from a JSON file. Works without network.
+#[derive(Debug, Clone)]
+pub struct StaticRates {
+    table: RateTable,
+}
+
+impl StaticRates {
+    pub fn new(table: RateTable) -> Self {
+        Self { table }
+    }
+
+    /// Reads rates in the format of `RateTable::from_json`.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(Self::new(RateTable::from_json(json)?))
+    }
+
+    /// Reads rates from a JSON file in the format of `RateTable::from_json`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        match std::fs::read_to_string(path.as_ref()) {
+            Ok(json) => Self::from_json(&json),
+            Err(e) => Err(Error::Provider(format!(
+                "can't read {}: {}.",
+                path.as_ref().display(),
+                e
+            ))),
+        }
+    }
+}
+
+impl RateProvider for StaticRates {
+    fn rates<'a>(&'a self, base: &'a str) -> BoxFuture<'a, Result<RateTable>> {
+        Box::pin(async move { from_base(self.table.clone(), base) })
+    }
+}
+
+/// Rates given by a function, for sources convers doesn't know.
+/// The function can return rates from any base, they are recalculated to the asked one.
+pub struct FnProvider<F> {
+    function: F,
+}
+
+impl<F> FnProvider<F>
+where
+    F: Fn(&str) -> Result<RateTable> + Send + Sync,
+{
+    pub fn new(function: F) -> Self {
+        Self { function }
+    }
+}
+
+impl<F> RateProvider for FnProvider<F>
+where
+    F: Fn(&str) -> Result<RateTable> + Send + Sync,
+{
+    fn rates<'a>(&'a self, base: &'a str) -> BoxFuture<'a, Result<RateTable>> {
+        Box::pin(async move { from_base((self.function)(base)?, base) })
+    }
+}
+
+/// Returns `$XDG_CACHE_HOME/convers`, or `~/.cache/convers` if the variable is not set.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_CACHE_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+    Some(dir.join("convers"))
+}
+
+/// Provider keeping rates of other provider in memory, and on disk if asked.
+/// Rates are asked again after the provider's next update time, or after the TTL.
+/// When the provider can't be reached, last known rates are used, see `RateTable::age`.
+pub struct RateCache<P> {
+    provider: P,
+    ttl: Duration,
+    dir: Option<PathBuf>,
+    tables: Mutex<HashMap<String, RateTable>>,
+}
+
+impl<P: RateProvider> RateCache<P> {
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            ttl: DEFAULT_TTL,
+            dir: None,
+            tables: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets time to keep rates when the provider doesn't tell when they are updated.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Keeps rates in files in the directory too, so they survive restarts.
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Keeps rates in the user's cache directory, see `default_cache_dir`.
+    pub fn with_default_dir(mut self) -> Self {
+        self.dir = default_cache_dir();
+        self
+    }
+
+    fn path(&self, base: &str) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(format!("rates-{}.json", base)))
+    }
+
+    /// Returns last known rates from memory or disk, fresh or not.
+    fn cached(&self, base: &str) -> Option<RateTable> {
+        if let Some(table) = self.tables.lock().unwrap().get(base) {
+            return Some(table.clone());
+        }
+        let json = std::fs::read_to_string(self.path(base)?).ok()?;
+        let table = RateTable::from_json(&json).ok()?;
+        self.tables
+            .lock()
+            .unwrap()
+            .insert(base.to_string(), table.clone());
+        Some(table)
+    }
+
+    fn store(&self, base: &str, table: &RateTable) {
+        self.tables
+            .lock()
+            .unwrap()
+            .insert(base.to_string(), table.clone());
+        // The cache on disk is optional, conversions work without it.
+        if let Some(path) = self.path(base) {
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            let _ = std::fs::write(path, table.to_json());
+        }
+    }
+}
+
+impl<P: RateProvider> RateProvider for RateCache<P> {
+    fn rates<'a>(&'a self, base: &'a str) -> BoxFuture<'a, Result<RateTable>> {
+        Box::pin(async move {
+            let base = base.to_uppercase();
+            // The base names the cache file, so only codes like `USD` are taken.
+            if base.len() < 3 || !base.bytes().all(|b| b.is_ascii_uppercase()) {
+                let error = ParseError::new("Invalid currency code.", 0..base.len())
+                    .expected(&["currency code"]);
+                return Err(Error::UnknownCurrency(error));
+            }
+            let cached = self.cached(&base);
+            if let Some(table) = cached.as_ref().filter(|t| t.is_fresh(self.ttl)) {
+                return Ok(table.clone());
+            }
+            match self.provider.rates(&base).await {
+                Ok(table) => {
+                    self.store(&base, &table);
+                    Ok(table)
+                }
+                Err(error) if error.is_transient() => cached.ok_or(error),
+                Err(error) => Err(error),
+            }
+        })
+    }
+}
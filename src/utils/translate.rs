@@ -1,72 +1,107 @@
-use anyhow::{bail, Context, Result};
+use regex::Regex;
 use regex_split::RegexSplit;
 use serde_json::Value;
-use std::{fmt, io::Error};
 
-#[derive(Debug)]
-pub enum ConvertError {
-    ReqwestError(Error),
-    CustomError(String),
+use crate::{Error, Result};
+
+/// Former error of the translator, all functions return `convers::Error` now.
+#[deprecated(note = "use `convers::Error`")]
+pub type ConvertError = Error;
+
+/// Returns is the text a language code like `en`, `uk` or `zh-CN`.
+fn is_language_code(code: &str) -> bool {
+    let regex = Regex::new(r"^(?i:[a-z]{2,3}|auto)(?:-[a-zA-Z]{2,4})?$").unwrap();
+    regex.is_match(code)
 }
 
-impl fmt::Display for ConvertError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ConvertError::ReqwestError(e) => write!(f, "Reqwest error: {}", e),
-            ConvertError::CustomError(e) => write!(f, "Custom error: {}", e),
-        }
-    }
+/// Translator using Google Translate API.
+#[derive(Debug, Clone)]
+pub struct Translator {
+    base_url: String,
+    client: reqwest::Client,
 }
 
-impl From<Error> for ConvertError {
-    fn from(error: Error) -> Self {
-        ConvertError::ReqwestError(error)
+impl Default for Translator {
+    fn default() -> Self {
+        Self {
+            base_url: "https://translate.googleapis.com".to_string(),
+            client: reqwest::Client::new(),
+        }
     }
 }
 
-pub struct Translator {}
-
 impl Translator {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
     }
-    pub async fn translate(&self, from: &str, to: &str, text: &str) -> Result<String> {
-        let url = format!(
-            "https://translate.googleapis.com/translate_a/single?client=gtx&sl={}&tl={}&dt=t&q={}",
-            from, to, text
-        );
 
-        let response = reqwest::get(&url).await?.json::<Value>().await?;
+    /// Uses other server with the same API, requests go to `{url}/translate_a/single`.
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Uses given client, with its proxy, timeouts and user agent.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub async fn translate(&self, from: &str, to: &str, text: &str) -> Result<String> {
+        for code in [from, to] {
+            if !is_language_code(code) {
+                return Err(Error::UnknownLanguage(code.to_string()));
+            }
+        }
+        let response = self
+            .client
+            .get(format!("{}/translate_a/single", self.base_url))
+            .query(&[
+                ("client", "gtx"),
+                ("sl", from),
+                ("tl", to),
+                ("dt", "t"),
+                ("q", text),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
         if let Some(array) = response.as_array() {
-            if let Some(nested_array) = array.get(0).and_then(|v| v.as_array()) {
-                if let Some(inner_array) = nested_array.get(0).and_then(|v| v.as_array()) {
+            if let Some(nested_array) = array.first().and_then(|v| v.as_array()) {
+                if let Some(inner_array) = nested_array.first().and_then(|v| v.as_array()) {
                     let str_vec: Vec<String> = inner_array
                         .iter()
                         .filter_map(|v| v.as_str().map(|s| s.to_string()))
                         .collect();
                     if str_vec.is_empty() {
-                        bail!("Invalid response");
+                        return Err(Error::Provider("the translation is empty.".to_string()));
                     }
 
                     return Ok(format!("\n [ {} -> {} ] \n\n {}", from, to, str_vec[0]));
                 }
             }
         }
-        bail!("Invalid response")
+        Err(Error::Provider(
+            "the response has no translation.".to_string(),
+        ))
     }
 
     /// Translates text from &String query.
     /// Example of query: `en to ru how are you?`.
-    pub async fn convert(&self, text: &String) -> anyhow::Result<String> {
-        let re = regex::Regex::new(r"(:|to)").unwrap();
+    pub async fn convert(&self, text: &str) -> Result<String> {
+        let re = Regex::new(r"(:|to)").unwrap();
         let parts: Vec<&str> = re.split_inclusive(text).collect();
         if parts.len() < 2 {
-            bail!("Invalid conversion query: error parsing parts.");
+            return Err(Error::InvalidQuery(
+                "Invalid conversion query: error parsing parts.".to_string(),
+            ));
         }
         let from_part = parts[0]
             .split_whitespace()
             .collect::<Vec<&str>>()
-            .get(0)
+            .first()
             .unwrap_or(&"en")
             .to_string()
             .replace(":", "");
@@ -75,7 +110,7 @@ impl Translator {
         let text_part: String = parts
             .iter()
             .skip(1)
-            .map(|x| *x)
+            .copied()
             .collect::<Vec<&str>>()
             .join(" ")
             .split_whitespace()
@@ -84,8 +119,6 @@ impl Translator {
             .unwrap_or(&Vec::new())
             .join(" ");
 
-        self.translate(&from_part, to_part, &text_part)
-            .await
-            .context("Translation failed")
+        self.translate(&from_part, to_part, &text_part).await
     }
 }
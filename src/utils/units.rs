@@ -1,8 +1,14 @@
 use std::ops::{Add, Div, Mul, Sub};
 
-use anyhow::{bail, Result};
+use crate::{Error, Result};
 use regex::Regex;
 
+use super::{
+    diagnostic::{part_error, part_offsets, ParseError},
+    format::{format_number, FormatOptions},
+    locale::Locale,
+};
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Unit {
     Meter,
@@ -55,6 +61,137 @@ pub enum Unit {
     SquareKilometer,
     Hectare,
     Acre,
+    Radian,
+    Degree,
+    Mile,
+    Yard,
+    Foot,
+}
+
+/// Unit symbols accepted in queries.
+const SYMBOLS: [(&str, Unit); 64] = [
+    ("m", Unit::Meter),
+    ("cm", Unit::Centimeter),
+    ("mm", Unit::Millimeter),
+    ("km", Unit::Kilometer),
+    ("dcm", Unit::Decimeter),
+    ("hm", Unit::Hectometer),
+    ("dm", Unit::Decameter),
+    ("kg", Unit::Kilogram),
+    ("g", Unit::Gram),
+    ("mg", Unit::Milligram),
+    ("µg", Unit::Microgram),
+    ("t", Unit::Ton),
+    ("lb", Unit::Pound),
+    ("oz", Unit::Ounce),
+    ("A", Unit::Ampere),
+    ("mA", Unit::Milliampere),
+    ("V", Unit::Volt),
+    ("mV", Unit::Millivolt),
+    ("W", Unit::Watt),
+    ("kW", Unit::Kilowatt),
+    ("J", Unit::Joule),
+    ("kJ", Unit::Kilojoule),
+    ("s", Unit::Second),
+    ("min", Unit::Minute),
+    ("h", Unit::Hour),
+    ("B", Unit::Byte),
+    ("KB", Unit::Kilobyte),
+    ("MB", Unit::Megabyte),
+    ("GB", Unit::Gigabyte),
+    ("TB", Unit::Terabyte),
+    ("PB", Unit::Petabyte),
+    ("px", Unit::Pixel),
+    ("rem", Unit::Rem),
+    ("em", Unit::Em),
+    ("°C", Unit::Celsius),
+    ("C", Unit::Celsius),
+    ("°F", Unit::Fahrenheit),
+    ("F", Unit::Fahrenheit),
+    ("K", Unit::Kelvin),
+    ("Pa", Unit::Pascal),
+    ("bar", Unit::Bar),
+    ("atm", Unit::Atmosphere),
+    ("m/s", Unit::MeterPerSecond),
+    ("km/h", Unit::KilometerPerHour),
+    ("mph", Unit::MilePerHour),
+    ("l", Unit::Liter),
+    ("L", Unit::Liter),
+    ("ml", Unit::Milliliter),
+    ("mL", Unit::Milliliter),
+    ("m3", Unit::CubicMeter),
+    ("m³", Unit::CubicMeter),
+    ("m2", Unit::SquareMeter),
+    ("m²", Unit::SquareMeter),
+    ("km2", Unit::SquareKilometer),
+    ("km²", Unit::SquareKilometer),
+    ("ha", Unit::Hectare),
+    ("ac", Unit::Acre),
+    ("acre", Unit::Acre),
+    ("rad", Unit::Radian),
+    ("deg", Unit::Degree),
+    ("°", Unit::Degree),
+    ("mi", Unit::Mile),
+    ("yd", Unit::Yard),
+    ("ft", Unit::Foot),
+];
+
+impl Unit {
+    /// Returns unit by its symbol, like `km` or `MB`.
+    pub fn from_symbol(symbol: &str) -> Option<Unit> {
+        SYMBOLS
+            .iter()
+            .find_map(|(s, unit)| (*s == symbol).then_some(*unit))
+    }
+
+    /// Returns all accepted unit symbols.
+    pub fn symbols() -> impl Iterator<Item = &'static str> {
+        SYMBOLS.iter().map(|(symbol, _)| *symbol)
+    }
+
+    /// Returns base unit of the product of two base units, like `V * A = W`.
+    pub fn times(self, other: Unit) -> Option<Unit> {
+        PRODUCTS.iter().find_map(|(a, b, product)| {
+            ((*a, *b) == (self, other) || (*b, *a) == (self, other)).then_some(*product)
+        })
+    }
+
+    /// Returns base unit of the quotient of two base units, like `m / s = m/s`.
+    pub fn per(self, other: Unit) -> Option<Unit> {
+        PRODUCTS.iter().find_map(|(a, b, product)| {
+            if *product != self {
+                None
+            } else if *a == other {
+                Some(*b)
+            } else if *b == other {
+                Some(*a)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Products of base units: `a * b = product`.
+const PRODUCTS: [(Unit, Unit, Unit); 5] = [
+    (Unit::Volt, Unit::Ampere, Unit::Watt),
+    (Unit::Watt, Unit::Second, Unit::Joule),
+    (Unit::Meter, Unit::Meter, Unit::SquareMeter),
+    (Unit::SquareMeter, Unit::Meter, Unit::CubicMeter),
+    (Unit::MeterPerSecond, Unit::Second, Unit::Meter),
+];
+
+/// Returns error for unknown unit after the number which ends at `len`.
+pub(crate) fn unit_error(query: &str, len: usize) -> ParseError {
+    let rest = &query[len..];
+    let start = len + rest.len() - rest.trim_start().len();
+    let unit_part = rest.trim();
+    ParseError::new(
+        "Invalid conversion query: error parsing unit part.",
+        start..start + unit_part.len(),
+    )
+    .expected(&["unit"])
+    .suggest(unit_part, Unit::symbols())
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -79,8 +216,8 @@ impl Measurement {
             Unit::Milligram => (self.value / 1_000_000.0, Unit::Kilogram),
             Unit::Microgram => (self.value / 1_000_000_000.0, Unit::Kilogram),
             Unit::Ton => (self.value * 1000.0, Unit::Kilogram),
-            Unit::Pound => (self.value * 0.453592, Unit::Kilogram),
-            Unit::Ounce => (self.value * 0.0283495, Unit::Kilogram),
+            Unit::Pound => (self.value * 0.453_592_37, Unit::Kilogram),
+            Unit::Ounce => (self.value * 0.028_349_523_125, Unit::Kilogram),
             Unit::Ampere => (self.value, Unit::Ampere),
             Unit::Milliampere => (self.value / 1000.0, Unit::Ampere),
             Unit::Volt => (self.value, Unit::Volt),
@@ -119,7 +256,12 @@ impl Measurement {
             Unit::SquareMeter => (self.value, Unit::SquareMeter),
             Unit::SquareKilometer => (self.value * 1_000_000.0, Unit::SquareMeter),
             Unit::Hectare => (self.value * 10_000.0, Unit::SquareMeter),
-            Unit::Acre => (self.value * 4_046.86, Unit::SquareMeter),
+            Unit::Acre => (self.value * 4_046.856_422_4, Unit::SquareMeter),
+            Unit::Radian => (self.value, Unit::Radian),
+            Unit::Degree => (self.value.to_radians(), Unit::Radian),
+            Unit::Mile => (self.value * 1_609.344, Unit::Meter),
+            Unit::Yard => (self.value * 0.9144, Unit::Meter),
+            Unit::Foot => (self.value * 0.3048, Unit::Meter),
         };
         Measurement {
             value,
@@ -143,8 +285,8 @@ impl Measurement {
             Unit::Milligram => base_value * 1_000_000.0,
             Unit::Microgram => base_value * 1_000_000_000.0,
             Unit::Ton => base_value / 1000.0,
-            Unit::Pound => base_value / 0.453592,
-            Unit::Ounce => base_value / 0.0283495,
+            Unit::Pound => base_value / 0.453_592_37,
+            Unit::Ounce => base_value / 0.028_349_523_125,
             Unit::Ampere => base_value,
             Unit::Milliampere => base_value * 1000.0,
             Unit::Volt => base_value,
@@ -180,7 +322,12 @@ impl Measurement {
             Unit::SquareMeter => base_value,
             Unit::SquareKilometer => base_value / 1_000_000.0,
             Unit::Hectare => base_value / 10_000.0,
-            Unit::Acre => base_value / 4_046.86,
+            Unit::Acre => base_value / 4_046.856_422_4,
+            Unit::Radian => base_value,
+            Unit::Degree => base_value.to_degrees(),
+            Unit::Mile => base_value / 1_609.344,
+            Unit::Yard => base_value / 0.9144,
+            Unit::Foot => base_value / 0.3048,
         };
         Measurement {
             value: target_value,
@@ -190,96 +337,74 @@ impl Measurement {
 
     /// Returns String formated value and unit.
     pub fn txt(&self) -> String {
-        format!("{} {:?}", self.value, self.unit)
+        self.txt_with(&FormatOptions::default())
+    }
+
+    /// Returns String formated value and unit using given format options.
+    pub fn txt_with(&self, options: &FormatOptions) -> String {
+        let name = format!("{:?}", self.unit);
+        options.with_name(format_number(self.value, options), &name, &name)
     }
 
     /// Returns is measurement in str valid.
-    pub fn is_this(query: &String) -> bool {
-        if let Ok(_) = Measurement::from_str(query) {
-            true
-        } else {
-            false
-        }
+    pub fn is_this(query: &str) -> bool {
+        Measurement::from_str(query).is_ok()
     }
 
     /// Converts String query to Measurement.
     /// Example: `1m to cm` returns `Measurement { value: 100.0, unit: Unit::Centimeter }` (don't forget that's wrapped in Result).
-    pub fn convert(query: &String) -> Result<Self> {
+    /// Conversions can be chained: `1 acre to m2 to acre`.
+    pub fn convert(query: &str) -> Result<Self> {
+        Measurement::convert_with(query, &Locale::default())
+    }
+
+    /// Same as `convert`, but numbers are read using separators of given locale.
+    pub fn convert_with(query: &str, locale: &Locale) -> Result<Self> {
         let regex = Regex::new(r"(:|to)").unwrap();
         let parts: Vec<&str> = regex.split(query).collect(); //
+        let offsets = part_offsets(&regex, query);
 
-        if parts.len() != 2 {
-            bail!("Invalid conversion query: error parsing parts.");
+        if parts.len() < 2 {
+            return Err(Error::InvalidQuery(
+                "Invalid conversion query: error parsing parts.".to_string(),
+            ));
         }
         let from_part = parts[0].split_whitespace().collect::<String>();
-        let to_part = parts[1].split_whitespace().collect::<String>();
-        let from = match Measurement::from_str(&from_part) {
+        let mut result = match Measurement::parse(&from_part, locale) {
             Ok(m) => m,
-            Err(_) => bail!("Invalid conversion query: error parsing from part."),
+            Err(e) => {
+                let message = "Invalid conversion query: error parsing from part.";
+                return Err(part_error(e, message, parts[0], 0));
+            }
         };
-        let to = match Measurement::from_str(&to_part) {
-            Ok(m) => m,
-            Err(_) => bail!("Invalid conversion query: error parsing to part"),
-        };
-        Ok(from.to_other(to.unit))
+        for (part, offset) in parts[1..].iter().zip(&offsets[1..]) {
+            let to_part = part.split_whitespace().collect::<String>();
+            let to = match Measurement::parse(&to_part, locale) {
+                Ok(m) => m,
+                Err(e) => {
+                    let message = "Invalid conversion query: error parsing to part";
+                    return Err(part_error(e, message, part, *offset));
+                }
+            };
+            result = result.to_other(to.unit);
+        }
+        Ok(result)
     }
 
-    /// Formats &String to Measurement.
-    pub fn from_str(query: &String) -> Result<Box<Self>> {
-        let mut new_length = Box::new(Measurement {
-            value: 0.,
-            unit: Unit::Meter,
-        });
-        let val_str = query
-            .chars()
-            .take_while(|c| c.is_digit(10) || c == &'.')
-            .collect::<String>();
-        match val_str.parse::<f64>() {
-            Ok(v) => new_length.value = v,
-            Err(_) => {}
-        };
-        let unit_part = query.chars().skip(val_str.len()).collect::<String>();
-        if unit_part.is_empty() {
-            bail!("Invalid conversion query: error parsing unit part.");
-        }
-        match unit_part.as_str() {
-            "m" => new_length.unit = Unit::Meter,
-            "cm" => new_length.unit = Unit::Centimeter,
-            "mm" => new_length.unit = Unit::Millimeter,
-            "km" => new_length.unit = Unit::Kilometer,
-            "dcm" => new_length.unit = Unit::Decimeter,
-            "hm" => new_length.unit = Unit::Hectometer,
-            "dm" => new_length.unit = Unit::Decameter,
-            "kg" => new_length.unit = Unit::Kilogram,
-            "g" => new_length.unit = Unit::Gram,
-            "mg" => new_length.unit = Unit::Milligram,
-            "µg" => new_length.unit = Unit::Microgram,
-            "t" => new_length.unit = Unit::Ton,
-            "lb" => new_length.unit = Unit::Pound,
-            "oz" => new_length.unit = Unit::Ounce,
-            "A" => new_length.unit = Unit::Ampere,
-            "mA" => new_length.unit = Unit::Milliampere,
-            "V" => new_length.unit = Unit::Volt,
-            "mV" => new_length.unit = Unit::Millivolt,
-            "W" => new_length.unit = Unit::Watt,
-            "kW" => new_length.unit = Unit::Kilowatt,
-            "J" => new_length.unit = Unit::Joule,
-            "kJ" => new_length.unit = Unit::Kilojoule,
-            "s" => new_length.unit = Unit::Second,
-            "min" => new_length.unit = Unit::Minute,
-            "h" => new_length.unit = Unit::Hour,
-            "B" => new_length.unit = Unit::Byte,
-            "KB" => new_length.unit = Unit::Kilobyte,
-            "MB" => new_length.unit = Unit::Megabyte,
-            "GB" => new_length.unit = Unit::Gigabyte,
-            "TB" => new_length.unit = Unit::Terabyte,
-            "PB" => new_length.unit = Unit::Petabyte,
-            "px" => new_length.unit = Unit::Pixel,
-            "rem" => new_length.unit = Unit::Rem,
-            "em" => new_length.unit = Unit::Em,
-            _ => bail!("Invalid conversion query: error parsing unit part."),
+    /// Formats &str to Measurement.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(query: &str) -> Result<Box<Self>> {
+        Measurement::parse(query, &Locale::default()).map(Box::new)
+    }
+
+    /// Parses measurement like `1,5km` or `1e6 m` using separators of given locale.
+    pub fn parse(query: &str, locale: &Locale) -> Result<Self> {
+        let (value, len) = locale.parse_prefix(query).unwrap_or((0., 0));
+        let unit_part = query[len..].trim();
+        match Unit::from_symbol(unit_part) {
+            Some(unit) => Ok(Measurement::new(value, unit)),
+            None => Err(Error::UnknownUnit(unit_error(query, len))),
         }
-        Ok(new_length)
     }
     pub fn new(value: f64, unit: Unit) -> Self {
         Self { value, unit }
@@ -291,7 +416,9 @@ impl Add for Measurement {
 
     fn add(self, other: Self) -> Result<Self> {
         if self.to_base().unit != other.to_base().unit {
-            bail!("Cannot add measurements with different units.");
+            return Err(Error::IncompatibleUnits(
+                "Cannot add measurements with different units.".to_string(),
+            ));
         }
         Ok(Self {
             value: self.value + other.to_other(self.unit).value,
@@ -305,7 +432,9 @@ impl Sub for Measurement {
 
     fn sub(self, other: Self) -> Result<Self> {
         if self.to_base().unit != other.to_base().unit {
-            bail!("Cannot subtract measurements with different units.");
+            return Err(Error::IncompatibleUnits(
+                "Cannot subtract measurements with different units.".to_string(),
+            ));
         }
         Ok(Self {
             value: self.value - other.to_other(self.unit).value,
@@ -319,7 +448,9 @@ impl Mul for Measurement {
 
     fn mul(self, other: Self) -> Result<Self> {
         if self.to_base().unit != other.to_base().unit {
-            bail!("Cannot subtract measurements with different units.");
+            return Err(Error::IncompatibleUnits(
+                "Cannot subtract measurements with different units.".to_string(),
+            ));
         }
         Ok(Self {
             value: self.value * other.to_other(self.unit).value,
@@ -333,7 +464,9 @@ impl Div for Measurement {
 
     fn div(self, other: Self) -> Result<Self> {
         if self.to_base().unit != other.to_base().unit {
-            bail!("Cannot subtract measurements with different units.");
+            return Err(Error::IncompatibleUnits(
+                "Cannot subtract measurements with different units.".to_string(),
+            ));
         }
         Ok(Self {
             value: self.value / other.to_other(self.unit).value,
@@ -1,15 +1,40 @@
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
 use anyhow::Result;
 use convers::{
-    convert::magic_convert,
+    convert::{magic_convert, magic_convert_exact, magic_convert_using},
     utils::{
-        calc::{eval, meval},
-        currency::{curr_convert, curr_convert_q},
+        calc::{
+            complex_convert, eval, eval_with, evaluate, factor_convert, float_convert,
+            float_convert_with, meval, meval_with, money_eval, radix_convert, solve, Number,
+        },
+        currency::{curr_convert, curr_convert_q, Converter, Currency, Decimal},
+        exact::ExactMeasurement,
+        format::{format_number, FormatOptions, Notation, Rounding},
+        history::{Date, EcbHistory, HistoricalRates, History},
+        i18n::Language,
+        iso4217,
+        locale::Locale,
+        rates::{
+            CoinGecko, Ecb, FnProvider, OpenErApi, RateCache, RateProvider, RateTable, StaticRates,
+        },
         translate::Translator,
         units::{Measurement, Unit},
     },
+    Error,
 };
 
 #[test]
+#[allow(clippy::unnecessary_to_owned)]
 fn check_length_unit() {
     let length_in_meters = Measurement {
         value: 1.0,
@@ -122,3 +147,1173 @@ fn check_eval() -> Result<()> {
     assert_eq!(5., eval(&String::from("15/3"))?);
     Ok(())
 }
+
+#[test]
+fn check_format() {
+    let default = FormatOptions::default();
+    assert_eq!(format_number(0.1 + 0.2, &default), "0.3");
+    assert_eq!(format_number(1e25, &default), "1e25");
+    assert_eq!(format_number(-0.0, &default), "0");
+
+    let fixed = FormatOptions {
+        decimals: Some(2),
+        trim_zeros: false,
+        ..Default::default()
+    };
+    assert_eq!(format_number(2.5, &fixed), "2.50");
+    assert_eq!(format_number(2.675, &fixed), "2.68");
+    assert_eq!(format_number(0.001, &fixed), "0.00");
+
+    let significant = FormatOptions {
+        significant: Some(3),
+        ..Default::default()
+    };
+    assert_eq!(format_number(123456.0, &significant), "123000");
+    assert_eq!(format_number(0.00123456, &significant), "0.00123");
+
+    let scientific = FormatOptions {
+        significant: Some(3),
+        notation: Notation::Scientific,
+        ..Default::default()
+    };
+    assert_eq!(format_number(123456.0, &scientific), "1.23e5");
+    assert_eq!(format_number(9.999, &scientific), "1e1");
+
+    let engineering = FormatOptions {
+        decimals: Some(1),
+        notation: Notation::Engineering,
+        ..Default::default()
+    };
+    assert_eq!(format_number(12345.0, &engineering), "12.3e3");
+    assert_eq!(format_number(999.96, &engineering), "1e3");
+    assert_eq!(format_number(0.00042, &engineering), "420e-6");
+
+    let even = FormatOptions {
+        decimals: Some(0),
+        rounding: Rounding::HalfEven,
+        ..Default::default()
+    };
+    assert_eq!(format_number(2.5, &even), "2");
+    assert_eq!(format_number(3.5, &even), "4");
+    let floor = FormatOptions {
+        decimals: Some(1),
+        rounding: Rounding::Floor,
+        ..Default::default()
+    };
+    assert_eq!(format_number(-1.21, &floor), "-1.3");
+    assert_eq!(format_number(1.29, &floor), "1.2");
+
+    assert_eq!(
+        Measurement::new(1.0 / 3.0, Unit::Kilometer).txt_with(&fixed),
+        "0.33 Kilometer"
+    );
+    assert_eq!(
+        Currency::new("UAH".to_string(), 41.2345678)
+            .unwrap()
+            .txt_with(&fixed),
+        "41.23 UAH"
+    );
+}
+
+#[test]
+fn check_locale() -> Result<()> {
+    let de = Locale::from_tag("de-DE").unwrap();
+    assert_eq!(
+        Measurement::parse("1,5 km", &de)?,
+        Measurement::new(1.5, Unit::Kilometer)
+    );
+    assert_eq!(
+        *Measurement::from_str("1 000 000 B")?,
+        Measurement::new(1_000_000., Unit::Byte)
+    );
+    assert_eq!(
+        *Measurement::from_str("1,000.5 m")?,
+        Measurement::new(1000.5, Unit::Meter)
+    );
+    assert_eq!(
+        *Measurement::from_str("1e6 m")?,
+        Measurement::new(1e6, Unit::Meter)
+    );
+    assert_eq!(
+        *Measurement::from_str("-2.5e-3km")?,
+        Measurement::new(-2.5e-3, Unit::Kilometer)
+    );
+    assert_eq!(
+        *Measurement::from_str("2em")?,
+        Measurement::new(2., Unit::Em)
+    );
+    assert_eq!(
+        Measurement::convert_with("1.500,25 m to km", &de)?,
+        Measurement::new(1.50025, Unit::Kilometer)
+    );
+    assert_eq!(
+        Currency::parse("1 234,5 uah", &Locale::RU)?.amount,
+        Decimal::new(12345, 1)
+    );
+    assert_eq!(
+        Currency::from_str("-12.5usd")?.amount,
+        Decimal::new(-125, 1)
+    );
+
+    assert_eq!(eval("-5*2+1e3")?, 990.);
+    assert_eq!(eval("2+3*4")?, 14.);
+    assert_eq!(eval("(2+3)*4")?, 20.);
+    assert_eq!(eval_with("1,5*2", &de)?, 3.);
+    assert_eq!(eval("1,000.5+1")?, 1001.5);
+    assert_eq!(
+        meval_with("1,5km+500m", &de)?,
+        Measurement::new(2., Unit::Kilometer)
+    );
+
+    let options = FormatOptions {
+        locale: de,
+        group_digits: true,
+        ..Default::default()
+    };
+    assert_eq!(format_number(1234567.891, &options), "1.234.567,891");
+    assert_eq!(
+        Measurement::new(0.25, Unit::Kilometer).txt_with(&options),
+        "0,25 Kilometer"
+    );
+    assert_eq!(
+        format_number(
+            1.5e-9,
+            &FormatOptions {
+                locale: Locale::FR,
+                ..Default::default()
+            }
+        ),
+        "1,5e-9"
+    );
+    Ok(())
+}
+
+#[test]
+fn check_localized_names() {
+    let km = |value: f64, language: Language| {
+        Measurement::new(value, Unit::Kilometer).txt_with(&FormatOptions::localized(language))
+    };
+    assert_eq!(km(0.25, Language::Russian), "0,25 километра");
+    assert_eq!(km(1., Language::Russian), "1 километр");
+    assert_eq!(km(3., Language::Russian), "3 километра");
+    assert_eq!(km(11., Language::Russian), "11 километров");
+    assert_eq!(km(21., Language::Ukrainian), "21 кілометр");
+    assert_eq!(km(0.25, Language::German), "0,25 Kilometer");
+    assert_eq!(km(0.25, Language::Spanish), "0,25 kilómetros");
+    assert_eq!(km(1., Language::Spanish), "1 kilómetro");
+    assert_eq!(km(1., Language::English), "1 kilometer");
+    assert_eq!(km(1.5, Language::English), "1.5 kilometers");
+    assert_eq!(km(1.5, Language::French), "1,5 kilomètre");
+    assert_eq!(km(2., Language::French), "2 kilomètres");
+    assert_eq!(
+        Currency::new("uah".to_string(), 5.)
+            .unwrap()
+            .txt_with(&FormatOptions::localized(Language::Ukrainian)),
+        "5 гривень"
+    );
+    assert_eq!(
+        Currency::new("XYZ".to_string(), 5.)
+            .unwrap()
+            .txt_with(&FormatOptions::localized(Language::German)),
+        "5 XYZ"
+    );
+}
+
+#[test]
+fn check_exact_conversion() -> Result<()> {
+    let acre = ExactMeasurement::convert("1 acre to m2 to acre")?;
+    assert_eq!(acre.unit, Unit::Acre);
+    assert!(acre.is_integer());
+    assert_eq!(acre.txt(), "1 Acre");
+    assert_eq!(
+        ExactMeasurement::convert("1 acre to m2")?.txt(),
+        "4046.8564224 SquareMeter"
+    );
+    assert_eq!(
+        ExactMeasurement::convert("1lb to g")?.txt(),
+        "453.59237 Gram"
+    );
+    assert_eq!(
+        ExactMeasurement::convert("100 mph to m/s")?.txt(),
+        "44.704 MeterPerSecond"
+    );
+    assert_eq!(
+        ExactMeasurement::convert("0 °F to K")?.txt(),
+        "255.372222222222 Kelvin"
+    );
+    assert_eq!(
+        ExactMeasurement::convert("0.1 m to cm to m")?.txt(),
+        "0.1 Meter"
+    );
+    assert!(ExactMeasurement::convert("1 m to kg").is_err());
+    assert!(ExactMeasurement::convert("180 deg to rad").is_err());
+
+    let third = ExactMeasurement::convert("1 h to min")?.to_other(Unit::Second);
+    assert_eq!(
+        third.to_measurement(),
+        Measurement::new(3600., Unit::Second)
+    );
+    let options = FormatOptions {
+        decimals: Some(3),
+        trim_zeros: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        ExactMeasurement::convert("1 oz to lb")?.txt_with(&options),
+        "0.063 Pound"
+    );
+    assert_eq!(
+        Measurement::convert("1 acre to m2 to acre")?,
+        Measurement::new(1., Unit::Acre)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_magic_convert_exact() -> Result<()> {
+    let options = FormatOptions::default();
+    let response = magic_convert_exact("1 acre to m2 to acre", &options).await?;
+    assert_eq!(response, "1 Acre");
+    let response = magic_convert_exact("180 deg to rad", &options).await?;
+    assert!(response.starts_with("3.14159"));
+    Ok(())
+}
+
+#[test]
+fn check_big_numbers() -> Result<()> {
+    let power = evaluate("2^100")?;
+    assert!(power.is_exact());
+    assert_eq!(power.txt(), "1267650600228229401496703205376");
+    assert_eq!(evaluate("20!")?.txt(), "2432902008176640000");
+    assert_eq!(
+        evaluate("12345678901234567890 + 1")?.txt(),
+        "12345678901234567891"
+    );
+    assert_eq!(evaluate("0.1 + 0.2")?.txt(), "0.3");
+    assert_eq!(evaluate("2**-2")?.txt(), "0.25");
+    assert_eq!(evaluate("-2^2")?.txt(), "-4");
+    assert_eq!(evaluate("2^3^2")?.txt(), "512");
+    assert_eq!(evaluate("sqrt(16/9)")?.txt(), "1.33333333333333");
+    assert!(evaluate("sqrt(16/9)")?.is_exact());
+    assert_eq!(evaluate("8^(1/3)")?.txt(), "2");
+
+    let approximate = evaluate("sqrt(2)")?;
+    assert!(!approximate.is_exact());
+    assert_eq!(approximate.txt(), "1.4142135623731");
+    assert!(!evaluate("sin(pi / 2)")?.is_exact());
+    assert_eq!(evaluate("sin(pi / 2)")?.txt(), "1");
+    assert!(evaluate("1/0").is_err());
+    assert!(evaluate("(-1)!").is_err());
+    assert_eq!(eval("2^10")?, 1024.);
+    // Exponents above the exact limit are computed as floats.
+    assert_eq!(eval("2^20000")?, f64::INFINITY);
+    assert_eq!(eval("1.5^20000")?, f64::INFINITY);
+    assert_eq!(eval("2^(-20000)")?, 0.);
+    // Huge exact results and literals are floats, so they don't take long.
+    let start = Instant::now();
+    assert_eq!(eval("(2^10000)^10000")?, f64::INFINITY);
+    assert_eq!(eval("10000!^10000")?, f64::INFINITY);
+    assert_eq!(eval("1e-10000000")?, 0.);
+    assert_eq!(eval("1e10000000")?, f64::INFINITY);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    Ok(())
+}
+
+#[test]
+fn check_complex() -> Result<()> {
+    assert_eq!(evaluate("sqrt(-4)")?.txt(), "2i");
+    assert_eq!(evaluate("(3+4i)*(1-2i)")?.txt(), "11 - 2i");
+    assert_eq!(evaluate("abs(3+4i)")?.txt(), "5");
+    assert_eq!(evaluate("conj(3+4j)")?.txt(), "3 - 4i");
+    assert_eq!(evaluate("i^2")?.txt(), "-1");
+    assert_eq!(evaluate("exp(i*pi)")?.txt(), "-1");
+    assert_eq!(evaluate("ln(-1)")?.txt(), "3.14159265358979i");
+    assert_eq!(evaluate("re(2-5i) + im(2-5i)")?.txt(), "-3");
+    assert_eq!(evaluate("arg(-1)")?.txt(), "3.14159265358979");
+    assert_eq!(evaluate("1/(2i)")?.txt(), "-0.5i");
+    assert!(eval("sqrt(-4)").is_err());
+
+    let options = FormatOptions {
+        decimals: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(
+        complex_convert("3+4i to polar")?.txt_with(&options),
+        "5 ∠ 53.13°"
+    );
+    assert_eq!(complex_convert("10 ∠ 90 to rect")?.txt(), "10i");
+    assert_eq!(
+        complex_convert("2∠-45 in rectangular")?.txt_with(&options),
+        "1.41 - 1.41i"
+    );
+    Ok(())
+}
+
+#[test]
+fn check_programmer_mode() -> Result<()> {
+    assert_eq!(evaluate("0xff + 0b1010 + 0o17")?.txt(), "280");
+    assert_eq!(evaluate("0xf0 | 0x0f")?.txt(), "255");
+    assert_eq!(evaluate("0xff & 0b1010")?.txt(), "10");
+    assert_eq!(evaluate("0xff ^ 0x0f")?.txt(), "240");
+    assert_eq!(evaluate("6 xor 3")?.txt(), "5");
+    assert_eq!(evaluate("2^10")?.txt(), "1024");
+    assert_eq!(evaluate("0x2 ** 10")?.txt(), "1024");
+    assert_eq!(evaluate("1 << 4 + 1")?.txt(), "32");
+    assert_eq!(evaluate("~0")?.txt(), "-1");
+    assert_eq!(evaluate("-16 >> 2")?.txt(), "-4");
+    assert!(evaluate("1.5 & 1").is_err());
+
+    assert_eq!(radix_convert("255 to hex")?.txt(), "0xff");
+    assert_eq!(radix_convert("0xff to bin")?.txt(), "0b11111111");
+    assert_eq!(radix_convert("0o17 to dec")?.txt(), "15");
+    assert_eq!(radix_convert("-1 as u16")?.txt(), "65535");
+    assert_eq!(radix_convert("255 as i8")?.txt(), "-1");
+    assert_eq!(radix_convert("-1 as i8 to hex")?.txt(), "0xff");
+    assert_eq!(radix_convert("5 to bin as u8")?.txt(), "0b00000101");
+    assert_eq!(radix_convert("-255 to hex")?.txt(), "-0xff");
+    assert_eq!(radix_convert("2^3 to hex")?.txt(), "0x8");
+    assert_eq!(radix_convert("0x2 ^ 8 to hex")?.txt(), "0x100");
+    assert_eq!(radix_convert("2^200 as u128")?.txt(), "0");
+    assert_eq!(radix_convert("6 xor 3 to bin")?.txt(), "0b101");
+    assert_eq!(radix_convert("0xf0 | 0x0f to hex")?.txt(), "0xff");
+    assert!(radix_convert("1.5 to hex").is_err());
+    assert!(radix_convert("1 to i7").is_err());
+    Ok(())
+}
+
+#[test]
+fn check_float_inspection() -> Result<()> {
+    let f = float_convert("0.1 to f32 bits")?;
+    assert_eq!(f.bits, 0x3dcccccd);
+    assert_eq!(f.exponent(), -4);
+    assert_eq!(f.mantissa(), 0x4ccccd);
+    assert_eq!(
+        f.txt(),
+        "0 01111011 10011001100110011001101 (sign +, exponent -4, mantissa 0x4ccccd, value 0.100000001490116119384765625, ULP 7.45058059692383e-9)"
+    );
+    assert_eq!(
+        float_convert("1.5 to f64 hex")?.hex_txt(),
+        "0x3ff8000000000000"
+    );
+    assert_eq!(float_convert("-2 as f32 hex")?.hex_txt(), "0xc0000000");
+
+    let one = float_convert("0x3f800000 as f32")?;
+    assert_eq!(one.value(), 1.);
+    assert_eq!(one.ulp(), f32::EPSILON as f64);
+    assert_eq!(
+        one.txt(),
+        "1 (sign +, exponent 0, mantissa 0x0, ULP 0.000000119209289550781)"
+    );
+    assert!(float_convert("0x1 as f32")?.is_subnormal());
+    assert_eq!(float_convert("0x1 as f32")?.exponent(), -126);
+    let zero = float_convert("-0 to f64 bits")?;
+    assert_eq!(zero.bits, 1 << 63);
+    assert_eq!(zero.exponent(), 0);
+    assert!(zero
+        .txt()
+        .contains("(sign -, exponent 0, mantissa 0x0, value -0"));
+    assert_eq!(float_convert("0 to f32")?.exponent(), 0);
+    assert_eq!(float_convert("0x7f800000 as f32")?.value(), f64::INFINITY);
+    assert_eq!(float_convert("0x7f7fffff as f32")?.ulp(), 2f64.powi(104));
+    assert_eq!(
+        float_convert("0.1 to f64")?.value_txt(),
+        "0.1000000000000000055511151231257827021181583404541015625"
+    );
+    // Exact decimals are rounded once, straight to f32.
+    assert_eq!(
+        float_convert("1.00000005960464477539062500001 to f32")?.bits,
+        0x3f800001
+    );
+    // Ties go to the even mantissa: 1 + 2^-24 is 1, 1 + 3 * 2^-24 is 1 + 2^-22.
+    assert_eq!(
+        float_convert("1.000000059604644775390625 to f32")?.bits,
+        0x3f800000
+    );
+    assert_eq!(
+        float_convert("1.000000178813934326171875 to f32")?.bits,
+        0x3f800002
+    );
+    assert_eq!(float_convert("2^-1074 to f64")?.bits, 1);
+    assert_eq!(float_convert("10^400 to f64")?.value(), f64::INFINITY);
+    assert_eq!(float_convert("1/3 to f64")?.value(), 1. / 3.);
+    let zero = float_convert_with("\u{2212}0,0 to f64", &Locale::RU)?;
+    assert!(zero.is_negative());
+    assert!(float_convert("0x1ffffffff as f32").is_err());
+    assert!(float_convert("sqrt(-1) to f64").is_err());
+    Ok(())
+}
+
+#[test]
+fn check_statistics() -> Result<()> {
+    assert_eq!(evaluate("mean(3, 5, 9)")?.txt(), "5.66666666666667");
+    assert_eq!(evaluate("sum(1,234)")?.txt(), "235");
+    assert_eq!(evaluate("median([7, 1, 3, 9])")?.txt(), "5");
+    assert_eq!(evaluate("median([7, 1, 3])")?.txt(), "3");
+    assert_eq!(
+        evaluate("variance(2, 4, 4, 4, 5, 5, 7, 9)")?.txt(),
+        "4.57142857142857"
+    );
+    assert_eq!(evaluate("stdev([1, 3])")?.txt(), "1.4142135623731");
+    assert_eq!(evaluate("stdev(2, 4)")?.txt(), "1.4142135623731");
+    assert_eq!(evaluate("product([1, 2, 3], 4)")?.txt(), "24");
+    assert_eq!(evaluate("percentile([1, 2, 3, 4, 5], 90)")?.txt(), "4.6");
+    assert_eq!(evaluate("sum([0.1, 0.2])")?.txt(), "0.3");
+    assert_eq!(evaluate("2 * mean(1, 2) + 1")?.txt(), "4");
+    assert_eq!(eval_with("mean(1,5; 2,5)", &Locale::DE)?, 2.);
+
+    let mean = meval("mean(3m, 250cm, 4000mm)")?;
+    assert_eq!(mean.unit, Unit::Meter);
+    assert!((mean.value - 9.5 / 3.).abs() < 1e-12);
+    assert_eq!(
+        meval("sum([1 km, 500 m])")?,
+        Measurement::new(1.5, Unit::Kilometer)
+    );
+    assert_eq!(
+        meval("median(1 kg, 3 kg, 2000 g)")?,
+        Measurement::new(2., Unit::Kilogram)
+    );
+
+    assert!(evaluate("mean()").is_err());
+    assert!(evaluate("variance(1)").is_err());
+    assert!(evaluate("percentile([1, 2], 101)").is_err());
+    assert!(matches!(
+        evaluate("percentile([5], log(0) - log(0))"),
+        Err(Error::Math(_))
+    ));
+    assert!(matches!(
+        evaluate("percentile([5], i)"),
+        Err(Error::Math(_))
+    ));
+    let values: Vec<String> = (1..=40)
+        .map(|i| match i % 3 {
+            0 => "exp(1000) - exp(1000)".to_string(),
+            _ => i.to_string(),
+        })
+        .collect();
+    let median = format!("median({})", values.join(", "));
+    assert!(matches!(evaluate(&median), Err(Error::Math(_))));
+    assert!(meval("mean(3m, 2kg)").is_err());
+    assert!(meval("variance(3m, 2m)").is_err());
+    assert!(evaluate("mean(1, 2m)").is_err());
+    assert!(evaluate("[1, 2] + 1").is_err());
+    Ok(())
+}
+
+#[test]
+fn check_number_theory() -> Result<()> {
+    assert_eq!(evaluate("25!")?.txt(), "15511210043330985984000000");
+    assert_eq!(evaluate("nCr(10, 3)")?.txt(), "120");
+    assert_eq!(
+        evaluate("nCr(100, 50)")?.txt(),
+        "100891344545564193334812497256"
+    );
+    assert_eq!(evaluate("nCr(3, 5)")?.txt(), "0");
+    assert_eq!(evaluate("nPr(10, 3)")?.txt(), "720");
+    assert_eq!(evaluate("gcd(12, 18, 30)")?.txt(), "6");
+    assert_eq!(evaluate("lcm(4, 6)")?.txt(), "12");
+    assert_eq!(evaluate("mod(-7, 3)")?.txt(), "2");
+    assert_eq!(evaluate("17 mod 5 + 1")?.txt(), "3");
+    assert_eq!(evaluate("7.5 mod 2")?.txt(), "1.5");
+    assert_eq!(evaluate("isprime(97)")?.txt(), "1");
+    assert_eq!(evaluate("isprime(2^61 - 1)")?.txt(), "1");
+    assert_eq!(evaluate("isprime(561)")?.txt(), "0");
+    assert_eq!(evaluate("fib(100)")?.txt(), "354224848179261915075");
+    assert_eq!(evaluate("fib(-6)")?.txt(), "-8");
+    assert_eq!(factor_convert("factor(360)")?.txt(), "2^3 × 3^2 × 5");
+    assert_eq!(factor_convert("factor(-12)")?.txt(), "-1 × 2^2 × 3");
+    assert_eq!(
+        factor_convert("factor 2^64 + 1")?.txt(),
+        "274177 × 67280421310721"
+    );
+    assert!(evaluate("gcd(1.5, 3)").is_err());
+    assert!(evaluate("nCr(-1, 2)").is_err());
+    assert!(evaluate("mod(1, 0)").is_err());
+    assert!(factor_convert("factor(0)").is_err());
+    let start = Instant::now();
+    assert!(factor_convert("factor 2^128 + 1").is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+    Ok(())
+}
+
+#[test]
+fn check_unit_expressions() -> Result<()> {
+    assert_eq!(meval("2 * 3m")?, Measurement::new(6., Unit::Meter));
+    assert_eq!(meval("3m * 2")?, Measurement::new(6., Unit::Meter));
+    assert_eq!(
+        meval("(1km + 200m) / 4")?,
+        Measurement::new(0.3, Unit::Kilometer)
+    );
+    assert_eq!(meval("2 (3 m)")?, Measurement::new(6., Unit::Meter));
+    assert_eq!(meval("3 m 20 cm")?, Measurement::new(3.2, Unit::Meter));
+    assert_eq!(meval("1 h 30 min")?.to_other(Unit::Minute).value, 90.);
+    let turn = meval("2pi rad")?;
+    assert_eq!(turn.unit, Unit::Radian);
+    assert!((turn.value - std::f64::consts::TAU).abs() < 1e-12);
+    assert!(
+        (meval("90°")?.to_other(Unit::Radian).value - std::f64::consts::FRAC_PI_2).abs() < 1e-12
+    );
+    assert!((Measurement::convert("180 deg to rad")?.value - std::f64::consts::PI).abs() < 1e-12);
+
+    assert_eq!(evaluate("2(3 + 4)")?.txt(), "14");
+    assert_eq!(evaluate("3 (2)(5)")?.txt(), "30");
+    assert!((eval("2pi")? - std::f64::consts::TAU).abs() < 1e-12);
+    assert!((eval("1/2pi")? - 1. / std::f64::consts::TAU).abs() < 1e-12);
+    assert_eq!(evaluate("6 xor 3")?.txt(), "5");
+    assert_eq!(evaluate("7 mod 4")?.txt(), "3");
+
+    assert!(meval("2 / 3m").is_err());
+    assert!(meval("3m + 2").is_err());
+    assert!(meval("3 m 2 kg").is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_expression_conversion() -> Result<()> {
+    let miles = meval("(5km + 300m) * 2 to mi")?;
+    assert_eq!(miles.unit, Unit::Mile);
+    assert!((miles.value - 10_600. / 1_609.344).abs() < 1e-12);
+    assert!((meval("sqrt(2) m to cm")?.value - 100. * 2f64.sqrt()).abs() < 1e-9);
+    assert_eq!(
+        meval("12V * 2A to kW")?,
+        Measurement::new(0.024, Unit::Kilowatt)
+    );
+    assert_eq!(meval("12V * 2A")?, Measurement::new(24., Unit::Watt));
+    assert_eq!(
+        meval("1 kW * 1 h in kJ")?,
+        Measurement::new(3600., Unit::Kilojoule)
+    );
+    assert_eq!(
+        meval("100 m / 10 s : km/h")?,
+        Measurement::new(36., Unit::KilometerPerHour)
+    );
+    assert_eq!(meval("2 m * 3 m")?, Measurement::new(6., Unit::SquareMeter));
+    assert_eq!(meval("5280 ft as mi")?.value, 1.);
+    assert_eq!(meval("24 W to V*A")?, Measurement::new(24., Unit::Watt));
+    assert_eq!(evaluate("10 m / 2 m")?.txt(), "5");
+    assert_eq!(
+        magic_convert("(5km + 300m) * 2 to mi").await?,
+        meval("(5km + 300m) * 2 to mi")?.txt()
+    );
+    assert!(meval("2 kg * 3 kg").is_err());
+    assert!(meval("3 m + 1 m to kg").is_err());
+    Ok(())
+}
+
+#[test]
+fn check_solve() -> Result<()> {
+    assert_eq!(solve("solve x: 2x + 3 = 11")?.roots, vec![4.]);
+    assert_eq!(solve("solve x: 2x + 3 = 11")?.txt(), "x = 4");
+    assert_eq!(solve("solve x: x^2 - 4 = 0")?.txt(), "x = -2 or x = 2");
+    assert_eq!(solve("solve y: y^2 - 4y + 4")?.roots, vec![2.]);
+    assert_eq!(solve("solve x: 1.5 = x / 1000000")?.roots, vec![1_500_000.]);
+    assert!((solve("solve x: cos(x) = x")?.roots[0] - 0.739085133215161).abs() < 1e-12);
+    let cubic = solve("solve x: x^3 - 6x^2 + 11x - 6 = 0")?;
+    assert_eq!(cubic.txt(), "x = 1 or x = 2 or x = 3");
+    assert_eq!(
+        solve("solve x: (x - 1) * (x - 1.1) = 0")?.roots,
+        vec![1., 1.1]
+    );
+    assert_eq!(
+        solve("solve x: (x - 1) * (x - 1.01) = 0")?.roots,
+        vec![1., 1.01]
+    );
+
+    let time = solve("solve t: 100km = 80km/h * t to min")?;
+    assert_eq!(time.unit, Some(Unit::Minute));
+    assert_eq!(time.txt(), "t = 75 Minute");
+    let length = solve("solve d: d / 2 s = 3 m/s")?;
+    assert_eq!(length.unit, Some(Unit::Meter));
+    assert_eq!(length.roots, vec![6.]);
+
+    assert!(solve("solve x: x^2 + 1 = 0").is_err());
+    assert!(solve("solve x: 1/x = 0").is_err());
+    assert!(solve("solve x: x = 1 = 2").is_err());
+    assert!(solve("solve x: 3 m = 2 kg").is_err());
+    assert!(matches!(solve("solve x: x = x"), Err(Error::Math(_))));
+    let periodic = solve("solve x: sin(x) = 0")?;
+    assert!(periodic.more);
+    assert_eq!(periodic.roots.len(), 10);
+    assert!(periodic.roots.iter().all(|x| x.abs() < 16.));
+    assert!(periodic.txt().ends_with(" or …"));
+    let error = solve("solve x: floor(x) = 3").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The equation holds for every value from 3 to 4."
+    );
+    let error = solve("solve x: x = 2e9").unwrap_err();
+    assert!(error.to_string().contains("±1e9"));
+    let error = solve("solve x: x! = 120").unwrap_err();
+    assert!(error.to_string().contains("Factorial"));
+    Ok(())
+}
+
+#[test]
+fn check_parse_errors() {
+    let parse_error = |error: Error| error.parse_error().cloned().unwrap();
+
+    let error = parse_error(Measurement::convert("5 kmh to m").unwrap_err());
+    assert_eq!(error.span, 2..5);
+    assert_eq!(error.suggestions[0], "km/h");
+    assert!(error.to_string().contains("Did you mean 'km/h'"));
+
+    let error = parse_error(Currency::parse("1 usdd", &Locale::default()).unwrap_err());
+    assert_eq!(error.suggestions[0], "USD");
+
+    let error = parse_error(evaluate("2 * (3 + 4").unwrap_err());
+    assert_eq!(error.span, 10..10);
+    assert_eq!(error.expected, vec![")"]);
+
+    let error = parse_error(evaluate("sqr(4) + 1").unwrap_err());
+    assert_eq!(error.span, 0..3);
+    assert_eq!(error.suggestions[0], "sqrt");
+
+    let error = parse_error(evaluate("2 + $").unwrap_err());
+    assert_eq!(error.span, 4..5);
+
+    let error = parse_error(solve("solve x: 2x = 3 kmh").unwrap_err());
+    assert_eq!(error.span, 16..19);
+}
+
+#[test]
+fn check_error_kinds() {
+    assert!(matches!(
+        Measurement::convert("5 kmh to m"),
+        Err(Error::UnknownUnit(_))
+    ));
+    assert!(matches!(
+        Currency::parse("1 usdd", &Locale::default()),
+        Err(Error::UnknownCurrency(_))
+    ));
+    assert!(matches!(evaluate("2 * (3"), Err(Error::Parse(_))));
+    assert!(matches!(evaluate("1/0"), Err(Error::Math(_))));
+    assert!(matches!(
+        meval("3 m + 2 kg"),
+        Err(Error::IncompatibleUnits(_))
+    ));
+    assert!(matches!(solve("solve x"), Err(Error::InvalidQuery(_))));
+
+    let error = evaluate("1/0").unwrap_err();
+    assert_eq!(error.to_string(), "Division by zero.");
+    assert!(!error.is_transient());
+    assert!(Error::Timeout.is_transient());
+}
+
+#[tokio::test]
+async fn check_rate_providers() -> Result<()> {
+    let json = r#"{"base_code": "USD", "rates": {"USD": 1, "EUR": 0.5, "UAH": 40}}"#;
+    let converter = Converter::new(StaticRates::from_json(json)?);
+    assert_eq!(
+        converter.convert("usd", "uah", 2.).await?.amount,
+        Decimal::from(80)
+    );
+    assert_eq!(
+        converter.convert("EUR", "UAH", 1.).await?.amount,
+        Decimal::from(80)
+    );
+    let currency = converter
+        .convert_query("3 eur to usd", &Locale::default())
+        .await?;
+    assert_eq!(currency.txt(), "6 USD");
+    assert!(matches!(
+        converter.convert("usd", "gbp", 1.).await,
+        Err(Error::Provider(_))
+    ));
+
+    let provider = FnProvider::new(|_: &str| {
+        let rates = [("PLN".to_string(), 4.)].into_iter().collect();
+        Ok(RateTable::new("usd", rates))
+    });
+    assert_eq!(provider.rates("PLN").await?.rate("USD"), Some(0.25));
+    let converter = Converter::new(provider);
+    let options = FormatOptions::default();
+    let result = magic_convert_using("8 pln to usd", &options, &converter).await?;
+    assert_eq!(result, "2 USD");
+
+    let xml = "<Cube time='2024-01-02'><Cube currency='USD' rate='1.0956'/>\
+               <Cube currency='JPY' rate='155.52'/></Cube>";
+    let table = RateTable::from_ecb_xml(xml)?;
+    assert_eq!(table.base, "EUR");
+    assert_eq!(table.rate("JPY"), Some(155.52));
+    Ok(())
+}
+
+/// Answers each request with the body for its path, sends the request lines back.
+fn serve(responses: Vec<(&'static str, &'static str)>) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().take(responses.len()) {
+            let mut stream = stream.unwrap();
+            let mut request = [0; 4096];
+            let len = stream.read(&mut request).unwrap();
+            let request = String::from_utf8_lossy(&request[..len]).to_string();
+            let line = request.lines().next().unwrap_or_default().to_string();
+            let (status, body) = responses
+                .iter()
+                .find(|(path, _)| line.contains(path))
+                .map_or(("404 Not Found", ""), |(_, body)| ("200 OK", *body));
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            sender.send(line).unwrap();
+        }
+    });
+    (url, receiver)
+}
+
+#[tokio::test]
+async fn check_base_url() -> Result<()> {
+    let (url, requests) = serve(vec![
+        (
+            "/v6/latest/USD",
+            r#"{"base_code": "USD", "rates": {"EUR": 0.9}}"#,
+        ),
+        (
+            "/eurofxref-daily.xml",
+            "<Cube currency='USD' rate='1.25'/><Cube currency='GBP' rate='0.8'/>",
+        ),
+        (
+            "/translate_a/single",
+            r#"[[["Hallo Welt","hello world",null,null,1]]]"#,
+        ),
+    ]);
+    let client = reqwest::Client::builder().no_proxy().build()?;
+
+    let provider = OpenErApi::new()
+        .with_base_url(&format!("{}/v6/", url))
+        .with_client(client.clone());
+    assert_eq!(provider.rates("usd").await?.rate("EUR"), Some(0.9));
+    assert_eq!(requests.recv()?, "GET /v6/latest/USD HTTP/1.1");
+
+    let provider = Ecb::new().with_base_url(&url).with_client(client.clone());
+    assert_eq!(provider.rates("GBP").await?.rate("USD"), Some(1.5625));
+    requests.recv()?;
+
+    let translator = Translator::new().with_base_url(&url).with_client(client);
+    let translated = translator.translate("en", "de", "hello world").await?;
+    assert!(translated.ends_with("Hallo Welt"));
+    let request = requests.recv()?;
+    assert!(request.contains("sl=en") && request.contains("q=hello+world"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_rate_cache() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("convers-cache-{}", std::process::id()));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let hour_ago = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() - 3600;
+    let provider = FnProvider::new(move |_: &str| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let rates = [("EUR".to_string(), 0.5)].into_iter().collect();
+        Ok(RateTable {
+            updated: Some(hour_ago),
+            ..RateTable::new("USD", rates)
+        })
+    });
+    let cache = RateCache::new(provider).with_dir(&dir);
+    assert_eq!(cache.rates("usd").await?.rate("EUR"), Some(0.5));
+    assert_eq!(cache.rates("USD").await?.rate("EUR"), Some(0.5));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Offline: stale rates from disk are used and their age is reported.
+    let offline = FnProvider::new(|_: &str| Err(Error::Timeout));
+    let converter = Converter::new(
+        RateCache::new(offline)
+            .with_ttl(Duration::ZERO)
+            .with_dir(&dir),
+    );
+    let currency = converter.convert("usd", "eur", 10.).await?;
+    assert_eq!(currency.amount, Decimal::from(5));
+    assert!(currency.age.unwrap() >= Duration::from_secs(3600));
+
+    let failing = FnProvider::new(|_: &str| Err(Error::Timeout));
+    let cache = RateCache::new(failing).with_dir(dir.join("empty"));
+    assert!(matches!(cache.rates("USD").await, Err(Error::Timeout)));
+    assert!(matches!(
+        cache.rates("../x").await,
+        Err(Error::UnknownCurrency(_))
+    ));
+    assert!(matches!(
+        cache.rates("us").await,
+        Err(Error::UnknownCurrency(_))
+    ));
+
+    let json = r#"{"base_code": "USD", "rates": {"EUR": 0.5}, "time_next_update_unix": 1}"#;
+    assert!(!RateTable::from_json(json)?.is_fresh(Duration::from_secs(3600)));
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_cross_rates() -> Result<()> {
+    let json = r#"{"base_code": "USD", "rates": {"EUR": 0.8, "UAH": 40, "GBP": 0.5}}"#;
+    let table = RateTable::from_json(json)?;
+    assert_eq!(table.cross_rate("EUR", "UAH"), Some(50.));
+    assert_eq!(table.cross_rate("uah", "gbp"), Some(0.0125));
+    assert_eq!(table.cross_rate("PLN", "PLN"), Some(1.));
+    assert_eq!(table.cross_rate("EUR", "PLN"), None);
+
+    let bases = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let asked = bases.clone();
+    let provider = FnProvider::new(move |base: &str| {
+        asked.lock().unwrap().push(base.to_string());
+        RateTable::from_json(json)
+    });
+    let converter = Converter::new(RateCache::new(provider));
+    assert_eq!(
+        converter.convert("eur", "uah", 2.).await?.amount,
+        Decimal::from(100)
+    );
+    assert_eq!(
+        converter.convert("gbp", "eur", 1.).await?.amount,
+        Decimal::new(16, 1)
+    );
+    assert_eq!(*bases.lock().unwrap(), vec!["USD"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_currency_registry() -> Result<()> {
+    let usd = iso4217::find("usd").unwrap();
+    assert_eq!((usd.numeric, usd.minor_units, usd.sign()), (840, 2, "$"));
+    assert_eq!(iso4217::find_numeric(392).unwrap().code, "JPY");
+    assert_eq!(iso4217::lookup("грн").unwrap().code, "UAH");
+    assert_eq!(iso4217::lookup("US Dollars").unwrap().code, "USD");
+    assert!(iso4217::lookup("kr").is_none());
+
+    let locale = Locale::default();
+    let parsed = Currency::parse("$5", &locale)?;
+    assert_eq!(
+        (parsed.currency.as_str(), parsed.amount),
+        ("USD", Decimal::from(5))
+    );
+    assert_eq!(Currency::parse("5bucks", &locale)?.currency, "USD");
+    assert_eq!(Currency::parse("€", &locale)?.currency, "EUR");
+    // Only a currency sign or alias may come before the amount.
+    let error = Currency::parse("abc5usd", &locale).unwrap_err();
+    assert_eq!(error.parse_error().unwrap().span, 0..3);
+
+    let json = r#"{"base_code": "USD", "rates": {"EUR": 0.9, "JPY": 151.234, "UAH": 40}}"#;
+    let converter = Converter::new(StaticRates::from_json(json)?);
+    let result = converter.convert_query("$5 to €", &locale).await?;
+    assert_eq!(result.txt(), "4.5 EUR");
+    for query in ["abc5 usd to eur", "x5 usd to eur"] {
+        let error = converter.convert_query(query, &locale).await.unwrap_err();
+        assert_eq!(error.parse_error().unwrap().span.start, 0);
+    }
+    let result = converter.convert_query("5 bucks to yen", &locale).await?;
+    assert_eq!(result.txt(), "756 JPY");
+    let result = converter
+        .convert_query("100 hryvnia in usd", &locale)
+        .await?;
+    assert_eq!(result.txt(), "2.5 USD");
+    assert_eq!(Currency::new("USD".to_string(), 1.23456)?.txt(), "1.23 USD");
+    let options = FormatOptions::default();
+    let result = magic_convert_using("100 грн in $", &options, &converter).await?;
+    assert_eq!(result, "2.5 USD");
+
+    // Unknown currencies are rejected before the provider is asked.
+    let offline = Converter::new(FnProvider::new(|_: &str| Err(Error::Timeout)));
+    assert!(matches!(
+        offline.convert("usd", "xyz", 1.).await,
+        Err(Error::UnknownCurrency(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_money() -> Result<()> {
+    let json = r#"{"base_code": "USD", "rates": {"EUR": 0.1, "JPY": 150}}"#;
+    let converter = Converter::new(StaticRates::from_json(json)?);
+    let sum = converter.convert("usd", "eur", 1.).await?.amount
+        + converter.convert("usd", "eur", 2.).await?.amount;
+    assert_eq!(sum, Decimal::new(3, 1));
+
+    let half = Currency::from_decimal("USD".to_string(), Decimal::new(2345, 3));
+    assert_eq!(
+        half.rounded(Rounding::HalfEven).amount,
+        Decimal::new(234, 2)
+    );
+    assert_eq!(half.rounded(Rounding::HalfUp).amount, Decimal::new(235, 2));
+    assert_eq!(
+        Currency::new("JPY".to_string(), 2.5)?
+            .rounded(Rounding::HalfEven)
+            .amount,
+        Decimal::from(2)
+    );
+
+    let symbols = FormatOptions {
+        symbols: true,
+        group_digits: true,
+        trim_zeros: false,
+        ..FormatOptions::default()
+    };
+    let amount = Decimal::new(123456, 2);
+    assert_eq!(
+        Currency::from_decimal("USD".to_string(), amount).txt_with(&symbols),
+        "$1,234.56"
+    );
+    assert_eq!(
+        Currency::from_decimal("EUR".to_string(), -amount).txt_with(&symbols),
+        "-€1,234.56"
+    );
+    let ru = FormatOptions {
+        locale: Locale::RU,
+        ..symbols.clone()
+    };
+    assert_eq!(
+        Currency::from_decimal("UAH".to_string(), amount).txt_with(&ru),
+        "1\u{a0}234,56 ₴"
+    );
+    assert_eq!(
+        Currency::new("ARS".to_string(), 5.)?.txt_with(&symbols),
+        "5.00 ARS"
+    );
+    assert!(matches!(
+        Currency::new("USD".to_string(), f64::NAN),
+        Err(Error::Math(_))
+    ));
+    assert!(matches!(
+        Currency::new("USD".to_string(), 1e30),
+        Err(Error::Math(_))
+    ));
+    let locale = Locale::default();
+    for query in [
+        "1e30 usd to eur",
+        "123456789012345678901234567890 usd to eur",
+    ] {
+        let result = converter.convert_query(query, &locale).await;
+        assert!(matches!(result, Err(Error::Math(_))));
+    }
+    assert!(converter
+        .convert_query("5.5.5 usd to eur", &locale)
+        .await
+        .is_err());
+    assert!(evaluate("5.5.5").is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_money_expressions() -> Result<()> {
+    let money = money_eval("5 usd + 3 eur - $1")?;
+    assert_eq!(money.currency(), None);
+    assert_eq!(money.amounts()[0], ("USD", Number::from_int(4)));
+    assert_eq!(evaluate("20% of 150")?, Number::from_int(30));
+
+    let json = r#"{"base_code": "USD", "rates": {"EUR": 0.8, "GBP": 0.5, "UAH": 40}}"#;
+    let converter = Converter::new(StaticRates::from_json(json)?);
+    let locale = Locale::default();
+    let result = converter.evaluate("5 usd + 4 eur to gbp", &locale).await?;
+    assert_eq!(
+        (result.currency.as_str(), result.amount),
+        ("GBP", Decimal::from(5))
+    );
+    let result = converter
+        .convert_query("(1200 usd / 12) to uah", &locale)
+        .await?;
+    assert_eq!(result.amount, Decimal::from(4000));
+    let result = converter
+        .convert_query("20% of 300 eur in usd", &locale)
+        .await?;
+    assert_eq!(result.amount, Decimal::from(75));
+    assert_eq!(
+        converter
+            .evaluate("2 * (€1.5 + 1 euro)", &locale)
+            .await?
+            .txt(),
+        "5 EUR"
+    );
+    let options = FormatOptions::default();
+    let result = magic_convert_using("5 usd + 3 eur to gbp", &options, &converter).await?;
+    assert_eq!(result, "4.38 GBP");
+
+    // Sums in one currency need no rates.
+    let offline = Converter::new(FnProvider::new(|_: &str| Err(Error::Timeout)));
+    assert_eq!(
+        offline.evaluate("1200 usd / 12", &locale).await?.amount,
+        Decimal::from(100)
+    );
+    assert!(matches!(
+        offline.evaluate("5 usd + 2 m", &locale).await,
+        Err(Error::Math(_) | Error::IncompatibleUnits(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_multi_target() -> Result<()> {
+    let json = r#"{"base_code": "USD", "rates": {"EUR": 0.9, "GBP": 0.8, "JPY": 150}}"#;
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let counter = fetches.clone();
+    let provider = FnProvider::new(move |_: &str| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        RateTable::from_json(json)
+    });
+    let converter = Converter::new(provider).with_favourites(&["eur", "jpy"]);
+    let locale = Locale::default();
+
+    let results = converter
+        .convert_query_many("100 usd to eur, gbp, jpy", &locale)
+        .await?;
+    let texts: Vec<String> = results.iter().map(Currency::txt).collect();
+    assert_eq!(texts, ["90 EUR", "80 GBP", "15000 JPY"]);
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+    let results = converter
+        .convert_query_many("100 usd to *favourites, gbp", &locale)
+        .await?;
+    let codes: Vec<&str> = results.iter().map(|c| c.currency.as_str()).collect();
+    assert_eq!(codes, ["EUR", "JPY", "GBP"]);
+    assert_eq!(
+        converter
+            .convert_many("eur", &["usd", "eur"], 9.)
+            .await?
+            .len(),
+        2
+    );
+    assert_eq!(
+        converter
+            .convert_query_many("5 usd to eur", &locale)
+            .await?
+            .len(),
+        1
+    );
+
+    let error = converter
+        .convert_query_many("100 usd to eur, gpb", &locale)
+        .await
+        .unwrap_err();
+    assert_eq!(error.parse_error().unwrap().span, 16..19);
+    assert!(matches!(
+        Converter::new(StaticRates::from_json(json)?)
+            .convert_query_many("1 usd to *favourites", &locale)
+            .await,
+        Err(Error::InvalidQuery(_))
+    ));
+
+    let options = FormatOptions::default();
+    let result = magic_convert_using("$10 to €, £", &options, &converter).await?;
+    assert_eq!(result, "9 EUR, 8 GBP");
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_historical_rates() -> Result<()> {
+    let date: Date = "2024-03-01".parse()?;
+    assert_eq!(
+        (date.days(), date.to_string()),
+        (19783, "2024-03-01".to_string())
+    );
+    assert!("2023-02-29".parse::<Date>().is_err());
+
+    let csv = "Date,USD,GBP,\n2024-03-01,1.25,0.5,\n2024-02-29,1.2,N/A,\n";
+    let history = History::from_csv(csv, "EUR")?;
+    assert_eq!(history.table(date).unwrap().rate("USD"), Some(1.25));
+    assert!(history.table(Date::new(2024, 3, 20).unwrap()).is_none());
+
+    let latest = r#"{"base_code": "USD", "rates": {"EUR": 0.5}}"#;
+    let converter = Converter::new(StaticRates::from_json(latest)?).with_history(history);
+    let locale = Locale::default();
+    // Saturday uses rates of Friday.
+    let result = converter
+        .convert_query("100 usd to eur on 2024-03-02", &locale)
+        .await?;
+    assert_eq!(result.amount, Decimal::from(80));
+    assert_eq!(
+        converter
+            .convert_query("100 usd to eur", &locale)
+            .await?
+            .amount,
+        Decimal::from(50)
+    );
+    let result = converter
+        .convert_at("eur", "usd", 10., Date::new(2024, 2, 29).unwrap())
+        .await?;
+    assert_eq!(result.amount, Decimal::from(12));
+    let results = converter
+        .convert_query_many("10 eur to usd, gbp on 2024-03-01", &locale)
+        .await?;
+    assert_eq!(results[1].txt(), "5 GBP");
+    assert!(matches!(
+        converter
+            .convert_query("1 usd to eur on 1990-01-01", &locale)
+            .await,
+        Err(Error::Provider(_))
+    ));
+    let error = converter
+        .convert_query("1 usd to eur on 2024-13-01", &locale)
+        .await
+        .unwrap_err();
+    assert_eq!(error.parse_error().unwrap().span, 16..26);
+
+    let xml = r#"<Cube><Cube time='2024-03-01'><Cube currency='USD' rate='1.08'/></Cube>
+        <Cube time='2024-02-29'><Cube currency='USD' rate='1.1'/></Cube></Cube>"#;
+    let (url, requests) = serve(vec![("/eurofxref-hist.xml", xml)]);
+    let client = reqwest::Client::builder().no_proxy().build()?;
+    let ecb = EcbHistory::new().with_base_url(&url).with_client(client);
+    let table = ecb.rates_at("USD", Date::new(2024, 2, 29).unwrap()).await?;
+    assert_eq!(table.rate("EUR"), Some(1. / 1.1));
+    assert!(ecb.rates_at("EUR", date).await.is_ok());
+    assert_eq!(requests.try_iter().count(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_crypto_and_metals() -> Result<()> {
+    let locale = Locale::default();
+    let offline = Converter::new(FnProvider::new(|_: &str| Err(Error::Timeout)))
+        .with_assets(FnProvider::new(|_: &str| Err(Error::Timeout)));
+    let result = offline.convert_query("100000 sats to btc", &locale).await?;
+    assert_eq!(result.txt(), "0.001 BTC");
+    let result = offline.convert_query("1 gwei to eth", &locale).await?;
+    assert_eq!(result.txt(), "0.000000001 ETH");
+    let result = offline.convert_query("0.01 ₿ to sats", &locale).await?;
+    assert_eq!(
+        (result.currency.as_str(), result.amount),
+        ("sat", Decimal::from(1_000_000))
+    );
+    assert_eq!(
+        offline.convert("wei", "gwei", 1e9).await?.amount,
+        Decimal::from(1)
+    );
+
+    let fiat = r#"{"base_code": "USD", "rates": {"EUR": 0.9}}"#;
+    let asked = Arc::new(AtomicUsize::new(0));
+    let counter = asked.clone();
+    let assets = FnProvider::new(move |_: &str| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        RateTable::from_json(r#"{"base_code": "USD", "rates": {"BTC": 0.00002, "XAU": 0.0005}}"#)
+    });
+    let converter = Converter::new(StaticRates::from_json(fiat)?).with_assets(assets);
+    assert_eq!(
+        converter.convert("usd", "eur", 10.).await?.amount,
+        Decimal::from(9)
+    );
+    assert_eq!(asked.load(Ordering::SeqCst), 0);
+    let result = converter.convert_query("0.01 btc to usd", &locale).await?;
+    assert_eq!(result.txt(), "500 USD");
+    let result = converter.convert_query("10 g gold to eur", &locale).await?;
+    assert_eq!(result.txt(), "578.71 EUR");
+    let result = converter.convert_query("1 oz gold in usd", &locale).await?;
+    assert_eq!(result.txt(), "2000 USD");
+    assert!(matches!(
+        offline.evaluate("10 g usd", &locale).await,
+        Err(Error::IncompatibleUnits(_))
+    ));
+
+    let json = r#"{"bitcoin": {"usd": 50000, "xau": 25}, "ethereum": {"usd": 2500}}"#;
+    let (url, requests) = serve(vec![("/simple/price", json)]);
+    let client = reqwest::Client::builder().no_proxy().build()?;
+    let coingecko = CoinGecko::new().with_base_url(&url).with_client(client);
+    let table = coingecko.rates("USD").await?;
+    assert_eq!(table.rate("ETH"), Some(0.0004));
+    assert_eq!(table.rate("XAU"), Some(0.0005));
+    assert!(requests.recv()?.contains("vs_currencies=usd%2Cxau%2Cxag"));
+    Ok(())
+}